@@ -1,47 +1,553 @@
 use crate::{
-    audio::{AudioClip, AudioCommand, AudioPlayback},
-    saving::{LoadError, SaveError, SavedState},
+    audio::{AudioClip, AudioCommand, AudioPlayback, ClipColor, ClipSettings},
+    duplicates,
+    files::{self, ImportMode},
+    history::{self, PlayRecord, PlayStats, ReportEntry, ReportFormat},
+    importers::ImportError,
+    pack::{Board, PackError},
+    query::{ParseError, Query, QueryContext},
+    saving::{self, LoadError, PlayingClip, SaveError, SavedState, Session, WindowState},
+    search,
+    settings::{self, Action, Settings},
+    shuffle::Shuffle,
+    sorting::{self, SortMode},
     style::{self, FONT_BYTES_BOLD, FONT_BYTES_REGULAR},
 };
 
-use iced::{executor, font, theme, time, Application, Command, Element, Subscription};
+use iced::{
+    event, executor, font,
+    keyboard::{self, key, Key, Modifiers},
+    mouse, theme, time,
+    widget::scrollable,
+    window, Application, Command, Element, Event, Subscription,
+};
 use kira::{
     sound::{streaming::StreamingSoundData, PlaybackState},
     AudioManager, AudioManagerSettings, DefaultBackend, PlaybackRate, Tween,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use youtube_dl::YoutubeDl;
 
 const TITLE: &'static str = "Soundboard";
+/// How long to wait before saving again after a save failed.
+const SAVE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+/// How long plays wait to be saved when nothing else changed, so playing
+/// clips in a row doesn't rewrite the config each time.
+const HISTORY_SAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
+/// How many edits can be undone.
+const UNDO_LIMIT: usize = 50;
+pub const WINDOW_SIZE: iced::Size = iced::Size::new(500.0, 800.0);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tab {
     pub name: String,
-    pub directory: std::path::PathBuf,
+    #[serde(flatten)]
+    pub kind: TabKind,
+    #[serde(default)]
+    pub show_metadata: bool,
+    #[serde(default)]
+    pub sort: SortMode,
+    /// Arrangement used by [`SortMode::Manual`] in directory tabs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub manual_order: Vec<std::path::PathBuf>,
+    #[serde(default)]
+    pub layout: ClipLayout,
+    #[serde(default = "default_grid_columns")]
+    pub grid_columns: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variant_groups: Vec<VariantGroup>,
+    /// Relative scroll position of the clip list.
+    #[serde(default)]
+    pub scroll: f32,
 
     #[serde(skip)]
     pub clips: Vec<AudioClip>,
+    /// Order the random pad plays this tab's clips in.
+    #[serde(skip)]
+    pub shuffle: Shuffle,
+    /// Tells the tab apart from others with the same contents while the app
+    /// runs, so undo gives each open tab back its own clips.
+    #[serde(skip, default = "next_tab_id")]
+    pub id: u64,
+}
+
+/// Several takes of the same sound that share one button, which plays a
+/// different take each time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantGroup {
+    pub name: String,
+    pub clips: Vec<std::path::PathBuf>,
+
+    #[serde(skip)]
+    pub shuffle: Shuffle,
+}
+
+impl VariantGroup {
+    /// Groups the given files under the name they start with, like "laugh"
+    /// for "laugh 1.mp3" and "laugh 2.mp3".
+    pub fn new(clips: Vec<std::path::PathBuf>) -> Self {
+        let stems: Vec<String> = clips
+            .iter()
+            .filter_map(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .collect();
+
+        let first = stems.first().map(String::as_str).unwrap_or_default();
+        let prefix = stems.iter().skip(1).fold(first, |prefix, stem| {
+            let len = prefix
+                .char_indices()
+                .zip(stem.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(prefix.len().min(stem.len()), |((idx, _), _)| idx);
+            &prefix[..len]
+        });
+        let prefix = prefix.trim_end_matches(|c: char| {
+            c.is_ascii_digit() || c.is_whitespace() || "-_(".contains(c)
+        });
+
+        Self {
+            name: if prefix.is_empty() { first } else { prefix }.to_owned(),
+            clips,
+            shuffle: Shuffle::default(),
+        }
+    }
+}
+
+/// How the clips of a tab are arranged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipLayout {
+    /// One row per clip.
+    #[default]
+    List,
+    /// Square pads in a fixed number of columns.
+    Grid,
+}
+
+fn default_grid_columns() -> u16 {
+    4
+}
+
+fn next_tab_id() -> u64 {
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Where the clips of a [`Tab`] come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TabKind {
+    /// Every audio file found directly inside a directory.
+    Directory { directory: std::path::PathBuf },
+    /// A hand-curated list of files from anywhere, in the order they were arranged.
+    Collection { collection: Vec<std::path::PathBuf> },
+    /// The clips of all directory tabs that match a saved [`Query`].
+    Smart { query: String },
+}
+
+impl Tab {
+    fn new(name: String, kind: TabKind) -> Self {
+        Self {
+            name,
+            kind,
+            show_metadata: false,
+            sort: SortMode::default(),
+            manual_order: vec![],
+            layout: ClipLayout::default(),
+            grid_columns: default_grid_columns(),
+            variant_groups: vec![],
+            scroll: 0.0,
+            clips: vec![],
+            shuffle: Shuffle::default(),
+            id: next_tab_id(),
+        }
+    }
+
+    pub fn new_directory(directory: std::path::PathBuf) -> Self {
+        let name = directory
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| directory.to_string_lossy().into_owned());
+
+        Self::new(name, TabKind::Directory { directory })
+    }
+
+    pub fn new_smart(name: String, query: String) -> Self {
+        Self::new(name, TabKind::Smart { query })
+    }
+
+    pub fn new_collection(name: String) -> Self {
+        Self {
+            sort: SortMode::Manual,
+            ..Self::new(name, TabKind::Collection { collection: vec![] })
+        }
+    }
+
+    /// Points a directory tab at another folder, carrying its manual order
+    /// over by file name so a moved folder keeps its arrangement.
+    pub fn set_directory(&mut self, folder: std::path::PathBuf) {
+        if let TabKind::Directory { directory } = &mut self.kind {
+            for entry in self.manual_order.iter_mut() {
+                if let Some(name) = entry.file_name() {
+                    *entry = folder.join(name);
+                }
+            }
+            *directory = folder;
+            self.load_clips();
+        }
+    }
+
+    pub fn directory(&self) -> Option<&std::path::Path> {
+        match &self.kind {
+            TabKind::Directory { directory } => Some(directory),
+            TabKind::Collection { .. } | TabKind::Smart { .. } => None,
+        }
+    }
+
+    pub fn is_collection(&self) -> bool {
+        matches!(self.kind, TabKind::Collection { .. })
+    }
+
+    pub fn is_smart(&self) -> bool {
+        matches!(self.kind, TabKind::Smart { .. })
+    }
+
+    pub fn load_clips(&mut self) {
+        self.clips = match &self.kind {
+            TabKind::Directory { directory } => load_audio_clips(directory.clone()),
+            TabKind::Collection { collection } => collection
+                .iter()
+                .filter(|path| path.is_file())
+                .filter_map(|path| readable_clip(path))
+                .collect(),
+            // evaluated against the other tabs whenever it is shown
+            TabKind::Smart { .. } => vec![],
+        };
+    }
+
+    /// Adds a clip where loading the tab would have put it: by file name in a
+    /// directory tab, in list order in a collection.
+    pub fn insert_clip(&mut self, clip: AudioClip) {
+        let index = match &self.kind {
+            TabKind::Directory { .. } => {
+                let name = file_name(&clip.path);
+                self.clips.partition_point(|other| {
+                    sorting::natural_cmp(&file_name(&other.path), &name).is_lt()
+                })
+            }
+            TabKind::Collection { collection } => {
+                let place = collection
+                    .iter()
+                    .position(|entry| *entry == clip.path)
+                    .unwrap_or(collection.len());
+                let before: BTreeSet<_> = collection[..place].iter().collect();
+                self.clips
+                    .iter()
+                    .take_while(|other| before.contains(&other.path))
+                    .count()
+            }
+            TabKind::Smart { .. } => return,
+        };
+
+        self.clips.insert(index, clip);
+    }
+
+    /// Returns the arrangement used when sorting manually; a collection's own
+    /// list doubles as its manual order.
+    pub fn manual_order(&self) -> &[std::path::PathBuf] {
+        match &self.kind {
+            TabKind::Collection { collection } => collection,
+            _ => &self.manual_order,
+        }
+    }
+
+    /// Moves the clip at `from` to the place of the clip at `to` in the manual order.
+    pub fn move_clip(&mut self, from: &std::path::Path, to: &std::path::Path) {
+        let mut clips: Vec<(usize, &AudioClip)> = self.clips.iter().map(|clip| (0, clip)).collect();
+        sorting::sort_clips(
+            &mut clips,
+            SortMode::Manual,
+            &PlayStats::default(),
+            self.manual_order(),
+        );
+        let mut order: Vec<std::path::PathBuf> = clips
+            .into_iter()
+            .map(|(_, clip)| clip.path.clone())
+            .collect();
+
+        // keep collection entries whose files are currently missing
+        if let TabKind::Collection { collection } = &self.kind {
+            let missing: Vec<_> = collection
+                .iter()
+                .filter(|path| !order.contains(path))
+                .cloned()
+                .collect();
+            order.extend(missing);
+        }
+
+        let (Some(from), Some(to)) = (
+            order.iter().position(|path| path == from),
+            order.iter().position(|path| path == to),
+        ) else {
+            return;
+        };
+        let entry = order.remove(from);
+        order.insert(to, entry);
+
+        match &mut self.kind {
+            TabKind::Collection { collection } => *collection = order,
+            _ => self.manual_order = order,
+        }
+    }
+
+    /// Appends the given audio files to a collection, skipping ones already in it.
+    pub fn add_to_collection(&mut self, paths: &[std::path::PathBuf]) {
+        if let TabKind::Collection { collection } = &mut self.kind {
+            for path in paths.iter().filter(|path| is_audio_file(path)) {
+                if !collection.contains(path) {
+                    collection.push(path.clone());
+                }
+            }
+            self.load_clips();
+        }
+    }
+
+    pub fn remove_from_collection(&mut self, path: &std::path::Path) {
+        if let TabKind::Collection { collection } = &mut self.kind {
+            collection.retain(|entry| entry != path);
+            self.clips.retain(|clip| clip.path != path);
+        }
+    }
+
+    /// Moves a clip of a collection `offset` places up (negative) or down.
+    pub fn move_in_collection(&mut self, path: &std::path::Path, offset: isize) {
+        if let TabKind::Collection { collection } = &mut self.kind {
+            if let Some(from) = collection.iter().position(|entry| entry == path) {
+                let to = from.saturating_add_signed(offset).min(collection.len() - 1);
+                let entry = collection.remove(from);
+                collection.insert(to, entry);
+                self.load_clips();
+            }
+        }
+    }
+}
+
+/// Options the app is launched with.
+#[derive(Debug, Clone, Default)]
+pub struct Flags {
+    /// The profile to open instead of the one used last.
+    pub profile: Option<String>,
+}
+
+/// A message shown above the board until the user dismisses it.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub text: String,
+    /// A button offered along with the message, as its label and the
+    /// message it sends.
+    pub action: Option<(String, Message)>,
+}
+
+/// A change to a clip's file waiting for the user to confirm it.
+#[derive(Debug, Clone)]
+pub enum FileEdit {
+    Rename {
+        path: std::path::PathBuf,
+        name: String,
+    },
+    /// Moves the file into the directory of the tab at the given index.
+    Move {
+        path: std::path::PathBuf,
+        tab: usize,
+    },
+    Trash {
+        path: std::path::PathBuf,
+    },
+}
+
+impl FileEdit {
+    pub fn path(&self) -> &std::path::Path {
+        match self {
+            FileEdit::Rename { path, .. }
+            | FileEdit::Move { path, .. }
+            | FileEdit::Trash { path } => path,
+        }
+    }
+}
+
+/// A finished change to a clip's file that can still be undone.
+#[derive(Debug, Clone)]
+pub enum FileOperation {
+    Moved {
+        from: std::path::PathBuf,
+        to: std::path::PathBuf,
+    },
+    Trashed {
+        original: std::path::PathBuf,
+        trashed: std::path::PathBuf,
+        snapshot: ClipSnapshot,
+    },
+}
+
+/// Files brought into a tab by dropping them onto the window.
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    pub tab: usize,
+    pub count: usize,
+    pub errors: Vec<String>,
+}
+
+/// What the board knew about a clip before it was trashed.
+#[derive(Debug, Clone)]
+pub struct ClipSnapshot {
+    favorite: bool,
+    settings: Option<ClipSettings>,
+    /// Places the clip had in manually ordered lists, as `(tab index, position)`.
+    positions: Vec<(usize, usize)>,
+    /// Variant groups the clip was a take of, as `(tab index, group index,
+    /// group name, position)`.
+    variant_groups: Vec<(usize, usize, String, usize)>,
+    hidden: bool,
 }
 
 #[allow(unused)]
 #[derive(Debug, Clone)]
 pub enum Message {
     FontLoaded(Result<(), font::Error>),
-    Saved(Result<(), SaveError>),
-    Loaded(Result<SavedState, LoadError>),
+    /// The config of the named profile was written.
+    Saved(String, Result<(), SaveError>),
+    /// The config of the named profile was read.
+    Loaded(String, Result<SavedState, LoadError>),
 
     SelectTab(usize),
+    /// Selects the tab `offset` places after (positive) or before the current one, wrapping around.
+    CycleTab(isize),
     CloseTab(usize),
+    ToggleTabMenu,
+    TabMenuQueryChanged(String),
+    UndoCloseTab,
+    DismissClosedTab,
+    StartTabRename(usize),
+    TabRenameChanged(String),
+    SubmitTabRename,
+    DuplicateTab(usize),
+    ChangeTabDirectory(usize),
+    TabDirectoryChosen(usize, Option<std::path::PathBuf>),
+    TabDragStart(usize),
+    TabDragOver(usize),
     NewTab,
     CreateTab(Option<std::path::PathBuf>),
+    ToggleNewTabMenu,
+    NewCollection,
+    NewSmartTab,
+    SmartQueryChanged(usize, String),
+    PickCollectionFiles,
+    AddToCollection(usize, Vec<std::path::PathBuf>),
+    RemoveFromCollection(usize, std::path::PathBuf),
+    MoveInCollection(usize, std::path::PathBuf, isize),
+    FilesHovered,
+    FilesHoveredLeft,
+    TabBarHovered(bool),
+    FileDropped(std::path::PathBuf),
+    Imported(usize, Vec<std::path::PathBuf>, Vec<String>),
+    SetImportMode(ImportMode),
+    DismissImport,
     RefreshClips,
+    ToggleMetadata,
+    ToggleSortMenu,
+    SetSortMode(SortMode),
+    DragStart(std::path::PathBuf),
+    DragOver(std::path::PathBuf),
+    DragEnd,
+    WindowResized(iced::Size),
+    WindowMoved(iced::Point),
+    /// The clip list was scrolled to the given relative position.
+    ClipsScrolled(f32),
+    /// The window was asked to close, which it does once everything is saved.
+    CloseRequested,
+    Close,
+    ToggleLayoutMenu,
+    SetLayout(ClipLayout),
+    SetGridColumns(u16),
+    EditClipLabel(std::path::PathBuf),
+    ClipLabelChanged(String),
+    SubmitClipLabel,
+    CancelClipLabel,
+    SelectFavorites,
+    PlayRandom,
+    PlayVariant(usize),
+    GroupAsVariants,
+    Ungroup(usize),
+    ToggleHistory,
+    ToggleDuplicates,
+    CompareAudioToggled(bool),
+    ScanDuplicates,
+    DuplicatesFound(Vec<Vec<std::path::PathBuf>>),
+    HideClip(std::path::PathBuf),
+    UnhideClip(std::path::PathBuf),
+    HideExtraCopies(usize),
+    RequestDelete(std::path::PathBuf),
+    ConfirmDelete,
+    CancelDelete,
+    StartRename(std::path::PathBuf),
+    RenameChanged(String),
+    RequestMove(std::path::PathBuf, usize),
+    RequestTrash(std::path::PathBuf),
+    ConfirmFileEdit,
+    CancelFileEdit,
+    UndoFileOperation,
+    DismissFileOperation,
+    ExportStats(ReportFormat),
+    StatsExported(Option<std::path::PathBuf>),
+    ToggleFavorite(std::path::PathBuf),
+
+    ToggleClipSelected(std::path::PathBuf),
+    SelectAllClips,
+    ClearSelection,
+    TagInputChanged(String),
+    AddTagToSelection,
+    RemoveTagFromSelection,
+    SetSelectionColor(Option<ClipColor>),
+    ToggleTagFilter(String),
+
+    SearchChanged(String),
+    SearchScopeToggled,
+    SearchSubmitted,
+    FocusSearch,
+    ClearSearch,
 
     SetDirty,
+    DismissNotification(usize),
+    RestoreBackup,
+    BackupChosen(Option<std::path::PathBuf>),
+    BackupLoaded(Result<SavedState, LoadError>),
+    ToggleProfileMenu,
+    SwitchProfile(String),
+    NewProfile,
+    NewProfileNameChanged(String),
+    CreateProfile,
+    CancelNewProfile,
+    /// Exports the tab at the given index, or every tab when there is none, as a board pack.
+    ExportPack(Option<usize>),
+    PackExported(Option<Result<std::path::PathBuf, PackError>>),
+    ImportPack,
+    PackImported(Option<Result<Board, PackError>>),
+    /// Imports a config or playlist of another soundboard app.
+    ImportBoard,
+    /// Imports a folder with a subfolder for each tab.
+    ImportCategories,
+    BoardImported(Option<Result<Board, ImportError>>),
+    /// A key combination was pressed, named like "Ctrl+Shift+K". Bound keys
+    /// are turned into their action's message before anything handles them.
+    KeyPressed(String),
+    /// Looks for changes to the settings file.
+    CheckSettings,
     VolumeToggled,
     VolumeChanged(f32),
     SpeedToggled,
     SpeedChanged(f64),
+    Undo,
+    Redo,
 
     AudioEvent(usize, AudioCommand),
     StartPlayback(AudioClip),
@@ -55,14 +561,207 @@ pub enum Message {
     DownloadFinished(Result<(), ()>),
 }
 
+impl Message {
+    /// Whether the message can change the board in a way [`Message::Undo`]
+    /// reverts. Changes to files on disk have their own undo and are left out.
+    pub fn is_edit(&self) -> bool {
+        matches!(
+            self,
+            Message::CloseTab(_)
+                | Message::UndoCloseTab
+                | Message::SubmitTabRename
+                | Message::DuplicateTab(_)
+                | Message::TabDirectoryChosen(..)
+                | Message::CreateTab(_)
+                | Message::NewCollection
+                | Message::NewSmartTab
+                | Message::SmartQueryChanged(..)
+                | Message::AddToCollection(..)
+                | Message::RemoveFromCollection(..)
+                | Message::MoveInCollection(..)
+                | Message::FileDropped(_)
+                | Message::SetImportMode(_)
+                | Message::ToggleMetadata
+                | Message::SetSortMode(_)
+                | Message::DragEnd
+                | Message::SetLayout(_)
+                | Message::SetGridColumns(_)
+                | Message::SubmitClipLabel
+                | Message::GroupAsVariants
+                | Message::Ungroup(_)
+                | Message::HideClip(_)
+                | Message::UnhideClip(_)
+                | Message::HideExtraCopies(_)
+                | Message::ToggleFavorite(_)
+                | Message::AddTagToSelection
+                | Message::RemoveTagFromSelection
+                | Message::SetSelectionColor(_)
+                | Message::PackImported(_)
+                | Message::BoardImported(_)
+                | Message::VolumeToggled
+                | Message::VolumeChanged(_)
+                | Message::SpeedToggled
+                | Message::SpeedChanged(_)
+        )
+    }
+
+    /// For messages that come in a quick series, like dragging a slider or
+    /// typing, that is undone all at once, returns which series it is part of.
+    fn edit_series(&self) -> Option<EditSeries> {
+        let tab = match self {
+            Message::SmartQueryChanged(index, _) => Some(*index),
+            Message::SetGridColumns(_) | Message::VolumeChanged(_) | Message::SpeedChanged(_) => {
+                None
+            }
+            _ => return None,
+        };
+
+        Some(EditSeries {
+            kind: std::mem::discriminant(self),
+            tab,
+        })
+    }
+
+    /// Whether the message finishes a series of edits, like letting go of a
+    /// slider or leaving the tab whose query was being typed.
+    fn ends_edit_series(&self) -> bool {
+        matches!(
+            self,
+            Message::SetDirty
+                | Message::SelectTab(_)
+                | Message::CycleTab(_)
+                | Message::SelectFavorites
+        )
+    }
+}
+
+/// A quick series of the same message editing the same thing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditSeries {
+    kind: std::mem::Discriminant<Message>,
+    /// The tab that is edited, for messages that edit one.
+    tab: Option<usize>,
+}
+
+/// The part of the state that undo and redo bring back. Tabs are kept
+/// without their clips, which are taken over from the open tabs or loaded
+/// again.
+#[derive(Debug, Clone)]
+pub struct BoardState {
+    tabs: Vec<Tab>,
+    current_tab: usize,
+    favorites: BTreeSet<std::path::PathBuf>,
+    clip_settings: BTreeMap<std::path::PathBuf, ClipSettings>,
+    hidden_clips: BTreeSet<std::path::PathBuf>,
+    import_mode: ImportMode,
+    volume_enabled: bool,
+    global_volume: f32,
+    global_speed: f64,
+    speed_enabled: bool,
+}
+
+impl PartialEq for BoardState {
+    /// Compares everything undo brings back, leaving out where tabs were
+    /// scrolled to.
+    fn eq(&self, other: &BoardState) -> bool {
+        self.tabs.len() == other.tabs.len()
+            && self
+                .tabs
+                .iter()
+                .zip(&other.tabs)
+                .all(|(a, b)| same_tab(a, b))
+            && self.current_tab == other.current_tab
+            && self.favorites == other.favorites
+            && self.clip_settings == other.clip_settings
+            && self.hidden_clips == other.hidden_clips
+            && self.import_mode == other.import_mode
+            && self.volume_enabled == other.volume_enabled
+            && self.global_volume == other.global_volume
+            && self.global_speed == other.global_speed
+            && self.speed_enabled == other.speed_enabled
+    }
+}
+
+/// Whether two tabs show the same clips the same way.
+fn same_tab(a: &Tab, b: &Tab) -> bool {
+    // listed in full so a new field has to be thought about here
+    let Tab {
+        name,
+        kind,
+        show_metadata,
+        sort,
+        manual_order,
+        layout,
+        grid_columns,
+        variant_groups,
+        scroll: _,
+        clips: _,
+        shuffle: _,
+        id: _,
+    } = a;
+
+    *name == b.name
+        && *kind == b.kind
+        && *show_metadata == b.show_metadata
+        && *sort == b.sort
+        && *manual_order == b.manual_order
+        && *layout == b.layout
+        && *grid_columns == b.grid_columns
+        && variant_groups.len() == b.variant_groups.len()
+        && variant_groups
+            .iter()
+            .zip(&b.variant_groups)
+            .all(|(a, b)| a.name == b.name && a.clips == b.clips)
+}
+
 pub enum SoundboardApp {
     Loading,
     Loaded(AppState),
 }
 
 pub struct AppState {
+    /// Name of the profile whose config is shown and saved.
+    pub profile: String,
+    /// Every profile, read when the profile menu opens.
+    pub profiles: Vec<String>,
+    pub profile_menu_open: bool,
+    /// The name entered for a profile about to be created.
+    pub new_profile_name: Option<String>,
     pub tabs: Vec<Tab>,
     pub current_tab: usize,
+    pub favorites: BTreeSet<std::path::PathBuf>,
+    pub favorites_selected: bool,
+    /// Relative scroll position of the favorites.
+    pub favorites_scroll: f32,
+    /// Order the random pad plays favorites in.
+    pub favorites_shuffle: Shuffle,
+    pub play_stats: PlayStats,
+    pub clip_settings: BTreeMap<std::path::PathBuf, ClipSettings>,
+    /// Content hashes of clip files, so the settings of a file that was
+    /// renamed or moved outside the app can be found again.
+    pub clip_hashes: BTreeMap<std::path::PathBuf, u64>,
+    pub selected_clips: BTreeSet<std::path::PathBuf>,
+    pub tag_input: String,
+    pub tag_filter: BTreeSet<String>,
+    pub history: Vec<PlayRecord>,
+    pub history_open: bool,
+    /// Clips left out of every tab, e.g. extra copies found by the duplicate scan.
+    pub hidden_clips: BTreeSet<std::path::PathBuf>,
+    pub duplicates_open: bool,
+    /// Groups of files with the same audio, once a scan has finished.
+    pub duplicate_groups: Option<Vec<Vec<std::path::PathBuf>>>,
+    pub scanning_duplicates: bool,
+    pub compare_audio: bool,
+    /// A file waiting for the user to confirm its deletion.
+    pub pending_delete: Option<std::path::PathBuf>,
+    pub file_edit: Option<FileEdit>,
+    pub last_file_operation: Option<FileOperation>,
+    pub file_error: Option<String>,
+    /// Whether files are being dragged over the window.
+    pub drop_hovered: bool,
+    pub tab_bar_hovered: bool,
+    pub import_mode: ImportMode,
+    pub last_import: Option<ImportSummary>,
 
     pub audio_manager: Option<AudioManager>,
     pub active_playbacks: BTreeMap<usize, AudioPlayback>,
@@ -73,15 +772,93 @@ pub struct AppState {
     pub global_speed: f64,
     pub speed_enabled: bool,
 
+    pub search_query: String,
+    pub search_all_tabs: bool,
+    pub new_tab_menu_open: bool,
+    pub tab_menu_open: bool,
+    /// Text the list of all tabs is filtered by.
+    pub tab_menu_query: String,
+    pub sort_menu_open: bool,
+    pub layout_menu_open: bool,
+    pub window_size: iced::Size,
+    /// Where the window is, once it has been moved.
+    pub window_position: Option<iced::Point>,
+    /// The clip whose label is being edited along with the text entered so far.
+    pub label_edit: Option<(std::path::PathBuf, String)>,
+    pub dragging: Option<std::path::PathBuf>,
+    pub drag_target: Option<std::path::PathBuf>,
+    /// The tab being renamed along with the name entered so far.
+    pub tab_rename: Option<(usize, String)>,
+    pub tab_dragging: Option<usize>,
+    pub tab_drag_target: Option<usize>,
+    /// The last closed tab and where it was, so closing it can be undone.
+    pub closed_tab: Option<(usize, Tab)>,
+
     pub saving: bool,
     pub dirty: bool,
+    /// When the play history first changed since it was last saved.
+    pub unsaved_plays_since: Option<std::time::Instant>,
+    /// False when the config on disk could not be loaded nor backed up, so
+    /// saving would destroy it.
+    pub can_save: bool,
+    /// When to try saving again after a save failed.
+    pub save_retry: Option<std::time::Instant>,
+    /// Set once the window was asked to close, which it does after a last save.
+    pub closing: bool,
+    /// The profile to switch to once everything is saved.
+    pub switching_to: Option<String>,
+    pub notifications: Vec<Notification>,
+    /// What the key bindings are, by action.
+    pub keys: BTreeMap<Action, Vec<String>>,
+    /// Fingerprint of the settings file when it was last read or written by
+    /// the app, `None` inside when there was no file, and `None` until it
+    /// was first looked at.
+    pub settings_fingerprint: Option<Option<u64>>,
+    /// The notification about the settings file not loading, until it does.
+    pub settings_error: Option<String>,
+    /// What the board was like before each edit, the last one at the end.
+    pub undo_stack: Vec<BoardState>,
+    /// Edits that were undone, the last one undone at the end.
+    pub redo_stack: Vec<BoardState>,
+    /// The series the last edit was part of, so the rest of it is undone
+    /// along with it.
+    pub last_edit: Option<EditSeries>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
+            profile: saving::DEFAULT_PROFILE.to_owned(),
+            profiles: vec![],
+            profile_menu_open: false,
+            new_profile_name: None,
             tabs: vec![],
             current_tab: 0,
+            favorites: Default::default(),
+            favorites_selected: false,
+            favorites_scroll: 0.0,
+            favorites_shuffle: Shuffle::default(),
+            play_stats: Default::default(),
+            clip_settings: Default::default(),
+            clip_hashes: Default::default(),
+            selected_clips: Default::default(),
+            tag_input: String::new(),
+            tag_filter: Default::default(),
+            history: vec![],
+            history_open: false,
+            hidden_clips: Default::default(),
+            duplicates_open: false,
+            duplicate_groups: None,
+            scanning_duplicates: false,
+            compare_audio: false,
+            pending_delete: None,
+            file_edit: None,
+            last_file_operation: None,
+            file_error: None,
+            drop_hovered: false,
+            tab_bar_hovered: false,
+            import_mode: ImportMode::default(),
+            last_import: None,
             audio_manager: Default::default(),
             active_playbacks: Default::default(),
             next_id: 0,
@@ -89,16 +866,381 @@ impl Default for AppState {
             global_volume: 1.0,
             global_speed: 1.0,
             speed_enabled: true,
+            search_query: String::new(),
+            search_all_tabs: false,
+            new_tab_menu_open: false,
+            tab_menu_open: false,
+            tab_menu_query: String::new(),
+            sort_menu_open: false,
+            layout_menu_open: false,
+            window_size: WINDOW_SIZE,
+            window_position: None,
+            label_edit: None,
+            dragging: None,
+            drag_target: None,
+            tab_rename: None,
+            tab_dragging: None,
+            tab_drag_target: None,
+            closed_tab: None,
             saving: false,
             dirty: false,
+            unsaved_plays_since: None,
+            can_save: true,
+            save_retry: None,
+            closing: false,
+            switching_to: None,
+            notifications: vec![],
+            keys: settings::default_keys(),
+            settings_fingerprint: None,
+            settings_error: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+            last_edit: None,
         }
     }
 }
 
 impl AppState {
+    /// Builds the state from a profile's loaded config.
+    pub fn from_saved(
+        profile: String,
+        state: SavedState,
+        audio_manager: Option<AudioManager>,
+    ) -> Self {
+        let mut app_state = AppState {
+            profile,
+            tabs: state.tabs,
+            current_tab: state.current_tab,
+            favorites: state.favorites,
+            play_stats: state.play_stats,
+            history: state.history,
+            clip_settings: state.clip_settings,
+            clip_hashes: state.clip_hashes,
+            hidden_clips: state.hidden_clips,
+            import_mode: state.import_mode,
+            audio_manager,
+            global_volume: state.global_volume,
+            global_speed: state.global_speed,
+            volume_enabled: state.session.volume_enabled,
+            speed_enabled: state.session.speed_enabled,
+            favorites_selected: state.session.favorites_selected,
+            favorites_scroll: state.session.favorites_scroll,
+            history_open: state.session.history_open,
+            duplicates_open: state.session.duplicates_open,
+            window_size: state.session.window.map_or(WINDOW_SIZE, |window| {
+                iced::Size::new(window.width, window.height)
+            }),
+            window_position: state
+                .session
+                .window
+                .and_then(|window| window.position)
+                .map(|(x, y)| iced::Point::new(x, y)),
+            ..Default::default()
+        };
+        if app_state.favorites_selected {
+            app_state.load_missing_clips(); // TODO: move to async
+        } else {
+            app_state.refresh_clips(); // TODO: move to async
+        }
+        for playing in &state.session.playing {
+            app_state.restore_playback(playing);
+        }
+
+        app_state
+    }
+
+    /// Returns the state to write to the config, the session included.
+    pub fn saved_state(&self) -> SavedState {
+        SavedState {
+            version: saving::VERSION,
+            tabs: self.tabs.to_vec(),
+            current_tab: self.current_tab,
+            global_volume: self.global_volume,
+            global_speed: self.global_speed,
+            favorites: self.favorites.clone(),
+            play_stats: self.play_stats.clone(),
+            history: self.history.clone(),
+            clip_settings: self.clip_settings.clone(),
+            // the others are only kept to save hashing them again
+            clip_hashes: self
+                .clip_hashes
+                .iter()
+                .filter(|(path, _)| self.clip_settings.contains_key(*path))
+                .map(|(path, hash)| (path.clone(), *hash))
+                .collect(),
+            hidden_clips: self.hidden_clips.clone(),
+            import_mode: self.import_mode,
+            session: Session {
+                volume_enabled: self.volume_enabled,
+                speed_enabled: self.speed_enabled,
+                window: Some(WindowState {
+                    width: self.window_size.width,
+                    height: self.window_size.height,
+                    position: self.window_position.map(|point| (point.x, point.y)),
+                }),
+                favorites_selected: self.favorites_selected,
+                favorites_scroll: self.favorites_scroll,
+                history_open: self.history_open,
+                duplicates_open: self.duplicates_open,
+                playing: self
+                    .active_playbacks
+                    .values()
+                    .filter(|playback| playback.handle.state() != PlaybackState::Stopped)
+                    .map(|playback| PlayingClip {
+                        path: playback.clip.path.clone(),
+                        position: playback.handle.position(),
+                        started: playback.started,
+                        played: playback.played.as_secs_f64(),
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    /// Returns the relative scroll position of the clips shown.
+    pub fn scroll(&self) -> f32 {
+        if self.favorites_selected {
+            self.favorites_scroll
+        } else {
+            self.get_current_tab().map_or(0.0, |tab| tab.scroll)
+        }
+    }
+
+    /// Remembers how far the clips shown are scrolled, unless they are
+    /// search results.
+    pub fn set_scroll(&mut self, scroll: f32) {
+        if self.is_searching() {
+            return;
+        }
+
+        if self.favorites_selected {
+            self.favorites_scroll = scroll;
+        } else if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.scroll = scroll;
+        }
+    }
+
     pub fn save(&mut self) {
         self.dirty = false;
+        self.unsaved_plays_since = None;
+        self.saving = false;
+        self.save_retry = None;
+    }
+
+    /// Keeps the changes that failed to save and tries again in a while.
+    pub fn save_failed(&mut self, error: &SaveError) {
+        if let Some(kind) = error.kind() {
+            println!("Saving failed with {:?}", kind);
+        }
+
         self.saving = false;
+        self.save_retry = Some(std::time::Instant::now() + SAVE_RETRY_DELAY);
+        self.notify(error.to_string(), None);
+    }
+
+    /// Whether anything changed since the last save, plays included.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty || self.unsaved_plays_since.is_some()
+    }
+
+    /// Whether there are changes to save and nothing keeps them from being
+    /// saved now. Plays alone are saved once they have waited a while.
+    pub fn should_save(&self) -> bool {
+        let plays_due = self
+            .unsaved_plays_since
+            .is_some_and(|since| since.elapsed() >= HISTORY_SAVE_DELAY);
+
+        (self.dirty || plays_due)
+            && !self.saving
+            && !self.closing
+            && self.can_save
+            && self
+                .save_retry
+                .is_none_or(|retry| std::time::Instant::now() >= retry)
+    }
+
+    /// Logs a message and shows it to the user, replacing an identical one
+    /// still on screen.
+    pub fn notify(&mut self, text: String, action: Option<(String, Message)>) {
+        println!("{}", text);
+
+        self.notifications
+            .retain(|notification| notification.text != text);
+        self.notifications.push(Notification { text, action });
+    }
+
+    /// Tells the user why the config could not be loaded and what became of it.
+    pub fn notify_load_error(&mut self, error: &LoadError) {
+        if let LoadError::File(file_error) = error {
+            println!("Loading failed with {:?}", file_error.kind);
+        }
+
+        let mut text = error.to_string();
+        match error.backup() {
+            Some(backup) => text.push_str(&format!(", a copy was kept at {}", backup.display())),
+            None if !error.can_overwrite() => {
+                text.push_str(", changes will not be saved until it can be read")
+            }
+            None => {}
+        }
+
+        self.notify(
+            text,
+            Some(("Restore backup...".to_owned(), Message::RestoreBackup)),
+        );
+    }
+
+    /// Returns the message of the action the key combination is bound to.
+    pub fn key_message(&self, key: &str) -> Option<Message> {
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|bound| bound == key))
+            .map(|(action, _)| action.message())
+    }
+
+    /// Returns the part of the state kept in the settings file.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            volume: self.global_volume,
+            volume_enabled: self.volume_enabled,
+            speed: self.global_speed,
+            speed_enabled: self.speed_enabled,
+            keys: self.keys.clone(),
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| (tab.name.clone(), tab.kind.clone()))
+                .collect(),
+        }
+    }
+
+    /// Takes over what the settings file says, returning whether anything
+    /// changed. Tabs are matched to the ones already open by their
+    /// contents, then by name, so they keep their layout and arrangement.
+    pub fn apply_settings(&mut self, settings: Settings) -> bool {
+        if self.settings() == settings {
+            return false;
+        }
+
+        self.global_volume = settings.volume;
+        self.volume_enabled = settings.volume_enabled;
+        self.update_playbacks_volume();
+        self.global_speed = settings.speed;
+        self.speed_enabled = settings.speed_enabled;
+        self.update_playbacks_speed();
+        self.keys = settings.keys;
+
+        let current: Vec<_> = self
+            .tabs
+            .iter()
+            .map(|tab| (tab.name.clone(), tab.kind.clone()))
+            .collect();
+        if current != settings.tabs {
+            let mut old: Vec<Option<Tab>> = std::mem::take(&mut self.tabs)
+                .into_iter()
+                .map(Some)
+                .collect();
+            let mut moved = BTreeMap::new();
+
+            for (name, kind) in settings.tabs {
+                let same_kind =
+                    |tab: &Tab| std::mem::discriminant(&tab.kind) == std::mem::discriminant(&kind);
+                let found = old
+                    .iter()
+                    .position(|tab| tab.as_ref().is_some_and(|tab| tab.kind == kind))
+                    .or_else(|| {
+                        old.iter().position(|tab| {
+                            tab.as_ref()
+                                .is_some_and(|tab| tab.name == name && same_kind(tab))
+                        })
+                    });
+
+                let tab = match found.and_then(|idx| Some((idx, old[idx].take()?))) {
+                    Some((idx, mut tab)) => {
+                        moved.insert(idx, self.tabs.len());
+                        if tab.kind != kind {
+                            tab.kind = kind;
+                            tab.clips.clear();
+                        }
+                        tab.name = name;
+                        tab
+                    }
+                    None => Tab::new(name, kind),
+                };
+                self.tabs.push(tab);
+            }
+
+            self.remap_tabs(|idx| moved.get(&idx).copied());
+            if self
+                .get_current_tab()
+                .is_some_and(|tab| tab.clips.is_empty())
+            {
+                self.refresh_clips(); // TODO: move to async
+            }
+        }
+
+        true
+    }
+
+    /// Reads the settings file if it changed since it was last read, and
+    /// creates it if there is none yet.
+    pub fn load_settings(&mut self) {
+        let path = SavedState::settings_path(&self.profile);
+        let fingerprint = Some(settings::fingerprint(&path));
+        if fingerprint == self.settings_fingerprint {
+            return;
+        }
+        self.settings_fingerprint = fingerprint;
+
+        match settings::load(&path) {
+            Ok(Some(settings)) => {
+                if let Some(error) = self.settings_error.take() {
+                    self.notifications
+                        .retain(|notification| notification.text != error);
+                }
+                if self.apply_settings(settings) {
+                    println!("Settings reloaded from {}", path.display());
+                    self.set_dirty();
+                }
+            }
+            Ok(None) => self.write_settings(),
+            Err(error) => {
+                let text = format!("The settings could not be loaded. {error}");
+                if self.settings_error.as_ref() != Some(&text) {
+                    if let Some(old) = self.settings_error.take() {
+                        self.notifications
+                            .retain(|notification| notification.text != old);
+                    }
+                    self.notify(text.clone(), None);
+                    self.settings_error = Some(text);
+                }
+            }
+        }
+    }
+
+    /// Makes the settings file match the state without reading it, for a
+    /// state that was just restored and takes precedence over the file.
+    pub fn overwrite_settings(&mut self) {
+        let path = SavedState::settings_path(&self.profile);
+        self.settings_fingerprint = Some(settings::fingerprint(&path));
+        self.write_settings();
+    }
+
+    /// Writes changes made in the app to the settings file, unless it was
+    /// edited since it was last read, in which case the edits come first.
+    pub fn write_settings(&mut self) {
+        let path = SavedState::settings_path(&self.profile);
+        if Some(settings::fingerprint(&path)) != self.settings_fingerprint
+            || self.settings_error.is_some()
+        {
+            return;
+        }
+
+        match settings::write(&path, &self.settings()) {
+            Ok(true) => self.settings_fingerprint = Some(settings::fingerprint(&path)),
+            Ok(false) => {}
+            Err(error) => println!("Could not write the settings: {}", error),
+        }
     }
 
     pub fn toggle_global_volume(&mut self) {
@@ -130,11 +1272,11 @@ impl AppState {
     }
 
     pub fn start_download(&mut self) -> Result<(), ()> {
-        if let Some(tab) = self.get_current_tab() {
+        if let Some(directory) = self.get_current_tab().and_then(Tab::directory) {
             println!("Starting download...");
 
             let output = YoutubeDl::new("https://www.youtube.com/watch?v=VFbhKZFzbzk")
-                .youtube_dl_path(tab.directory.clone())
+                .youtube_dl_path(directory)
                 .run()
                 .unwrap();
             let title = output.into_single_video().unwrap().title;
@@ -146,64 +1288,849 @@ impl AppState {
     }
 
     pub fn refresh_clips(&mut self) {
-        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
-            tab.clips = load_audio_clips(tab.directory.clone());
+        if self.get_current_tab().is_some_and(Tab::is_smart) {
+            self.refresh_all_clips();
+        } else if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.load_clips();
+            self.relink_clip_settings();
             println!("Clips refreshed.");
         } else {
             println!("No clips to refresh.");
         }
     }
 
-    fn update_playbacks_volume(&mut self) {
-        let volume = self.get_global_volume().log10() * 20.0;
-        for (_, playback) in self.active_playbacks.iter_mut() {
-            let _ = playback.handle.set_volume(volume, Tween::default());
+    pub fn refresh_all_clips(&mut self) {
+        for tab in self.tabs.iter_mut() {
+            tab.load_clips();
         }
+        self.relink_clip_settings();
+        println!("All clips refreshed.");
     }
 
-    fn update_playbacks_speed(&mut self) {
-        let speed = self.get_global_speed();
-        for (_, playback) in self.active_playbacks.iter_mut() {
-            let _ = playback
-                .handle
-                .set_playback_rate(PlaybackRate(speed), Tween::default());
+    /// Loads the clips of every tab that has not been scanned yet.
+    pub fn load_missing_clips(&mut self) {
+        for tab in self.tabs.iter_mut().filter(|tab| tab.clips.is_empty()) {
+            tab.load_clips();
         }
+        self.relink_clip_settings();
     }
 
-    pub fn update_playbacks(&mut self) {
-        self.active_playbacks.retain(|_id, playback| {
-            if playback.handle.state() == PlaybackState::Stopped {
-                false
-            } else {
-                true
+    /// Hands the settings of files that are gone to loaded clips with the same
+    /// contents, which are those files renamed or moved outside the app.
+    /// Remembers the contents of every clip with settings for next time.
+    fn relink_clip_settings(&mut self) {
+        let unhashed: Vec<_> = self
+            .clip_settings
+            .keys()
+            .filter(|path| !self.clip_hashes.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in unhashed {
+            if let Some(hash) = duplicates::content_hash(&path) {
+                self.clip_hashes.insert(path, hash);
             }
-        });
-    }
+        }
 
-    pub fn stop_all_playbacks(&mut self) {
-        for (_, playback) in self.active_playbacks.iter_mut() {
-            let _ = playback.handle.stop(Tween::default());
+        let mut gone: BTreeMap<u64, std::path::PathBuf> = self
+            .clip_settings
+            .keys()
+            .filter(|path| !path.exists())
+            .filter_map(|path| Some((*self.clip_hashes.get(path)?, path.clone())))
+            .collect();
+        if gone.is_empty() {
+            return;
         }
-    }
 
-    pub fn start_playback(&mut self, clip: AudioClip) {
-        let sound_data = StreamingSoundData::from_file(clip.clone().path).unwrap();
+        let candidates: BTreeSet<std::path::PathBuf> = self
+            .tabs
+            .iter()
+            .flat_map(|tab| &tab.clips)
+            .map(|clip| &clip.path)
+            .filter(|path| !self.clip_settings.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in candidates {
+            let hash = match self.clip_hashes.get(&path) {
+                Some(hash) => *hash,
+                None => {
+                    let Some(hash) = duplicates::content_hash(&path) else {
+                        continue;
+                    };
+                    self.clip_hashes.insert(path.clone(), hash);
+                    hash
+                }
+            };
 
-        let mut sound_handle = self
-            .audio_manager
-            .as_mut()
-            .unwrap()
-            .play(sound_data)
-            .unwrap();
+            if let Some(old) = gone.remove(&hash) {
+                println!("Settings of {} moved to {}", old.display(), path.display());
+                if let Some(settings) = self.clip_settings.remove(&old) {
+                    self.clip_settings.insert(path, settings);
+                }
+                self.clip_hashes.remove(&old);
+                self.set_dirty();
+            }
+            if gone.is_empty() {
+                break;
+            }
+        }
+    }
 
-        sound_handle.set_playback_rate(self.get_global_speed(), Tween::default());
+    pub fn is_searching(&self) -> bool {
+        !self.search_query.trim().is_empty()
+    }
 
-        let volume = self.get_global_volume().log10() * 20.0;
-        sound_handle.set_volume(volume, Tween::default());
+    pub fn is_favorite(&self, clip: &AudioClip) -> bool {
+        self.favorites.contains(&clip.path)
+    }
+
+    pub fn toggle_favorite(&mut self, path: &std::path::Path) {
+        if !self.favorites.remove(path) {
+            self.favorites.insert(path.to_path_buf());
+        }
+    }
+
+    /// Returns every clip of every tab as `(tab index, clip)` pairs, leaving
+    /// out hidden ones.
+    pub fn all_clips(&self) -> impl Iterator<Item = (usize, &AudioClip)> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, tab)| tab.clips.iter().map(move |clip| (idx, clip)))
+            .filter(|(_, clip)| !self.hidden_clips.contains(&clip.path))
+    }
+
+    /// Returns the clip the given key combination was assigned to, if its
+    /// file is still there.
+    pub fn hotkey_clip(&self, hotkey: &str) -> Option<AudioClip> {
+        let path = self
+            .clip_settings
+            .iter()
+            .find(|(_, settings)| settings.hotkey.as_deref() == Some(hotkey))
+            .map(|(path, _)| path)
+            .filter(|path| path.is_file())?;
+
+        let loaded = self
+            .tabs
+            .iter()
+            .flat_map(|tab| tab.clips.iter())
+            .find(|clip| clip.path == *path);
+        match loaded {
+            Some(clip) => Some(clip.clone()),
+            None => readable_clip(path),
+        }
+    }
+
+    /// Returns the path of every file shown by a directory or collection tab,
+    /// hidden ones included, each listed once.
+    pub fn all_clip_paths(&mut self) -> Vec<std::path::PathBuf> {
+        self.load_missing_clips();
+
+        let mut seen = BTreeSet::new();
+        self.tabs
+            .iter()
+            .flat_map(|tab| tab.clips.iter())
+            .filter(|clip| seen.insert(&clip.path))
+            .map(|clip| clip.path.clone())
+            .collect()
+    }
+
+    pub fn hide_clip(&mut self, path: &std::path::Path) {
+        self.hidden_clips.insert(path.to_path_buf());
+        self.selected_clips.remove(path);
+    }
+
+    /// Hides every file of a duplicate group except the first.
+    pub fn hide_extra_copies(&mut self, group: usize) {
+        let extra: Vec<_> = self
+            .duplicate_groups
+            .iter()
+            .flatten()
+            .nth(group)
+            .into_iter()
+            .flat_map(|paths| paths.iter().skip(1).cloned())
+            .collect();
+
+        for path in extra {
+            self.hide_clip(&path);
+        }
+    }
+
+    /// Deletes a file from disk and drops it from the board.
+    pub fn delete_clip(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::remove_file(path)?;
+        self.forget_clip(path);
+
+        Ok(())
+    }
+
+    /// Carries out the confirmed file edit, remembering it so it can be undone.
+    pub fn apply_file_edit(&mut self) -> std::io::Result<()> {
+        let Some(edit) = self.file_edit.take() else {
+            return Ok(());
+        };
+
+        let operation = match edit {
+            FileEdit::Rename { path, name } => {
+                let to = files::rename_file(&path, &name)?;
+                self.relocate_clip(&path, &to);
+                FileOperation::Moved { from: path, to }
+            }
+            FileEdit::Move { path, tab } => {
+                let Some(directory) = self.tabs.get(tab).and_then(Tab::directory) else {
+                    return Ok(());
+                };
+                let to = files::move_file(&path, directory)?;
+                self.relocate_clip(&path, &to);
+                FileOperation::Moved { from: path, to }
+            }
+            FileEdit::Trash { path } => {
+                let snapshot = self.snapshot_clip(&path);
+                let trashed = files::trash_file(&path)?;
+                self.forget_clip(&path);
+                FileOperation::Trashed {
+                    original: path,
+                    trashed,
+                    snapshot,
+                }
+            }
+        };
+        self.last_file_operation = Some(operation);
+
+        Ok(())
+    }
+
+    /// Sends trashed clips on to the system trash once trashing them can't be
+    /// undone anymore.
+    pub fn release_trash(&self) {
+        let keep = match &self.last_file_operation {
+            Some(FileOperation::Trashed { trashed, .. }) => Some(trashed.as_path()),
+            _ => None,
+        };
+        files::release_trash(keep);
+    }
+
+    /// Reverts the last file edit by moving the file back where it was.
+    pub fn undo_file_operation(&mut self) -> std::io::Result<()> {
+        let Some(operation) = self.last_file_operation.take() else {
+            return Ok(());
+        };
+
+        match operation {
+            FileOperation::Moved { from, to } => {
+                files::move_file_to(&to, &from)?;
+                self.relocate_clip(&to, &from);
+            }
+            FileOperation::Trashed {
+                original,
+                trashed,
+                snapshot,
+            } => {
+                files::move_file_to(&trashed, &original)?;
+                self.restore_clip(&original, snapshot);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Points everything that refers to a clip at the new location of its file.
+    fn relocate_clip(&mut self, from: &std::path::Path, to: &std::path::Path) {
+        let relocated = readable_clip(to);
+
+        for tab in self.tabs.iter_mut() {
+            for entry in tab.manual_order.iter_mut().filter(|entry| *entry == from) {
+                *entry = to.to_path_buf();
+            }
+            for group in tab.variant_groups.iter_mut() {
+                for entry in group.clips.iter_mut().filter(|entry| *entry == from) {
+                    *entry = to.to_path_buf();
+                }
+            }
+
+            let existing = tab.clips.iter().position(|clip| clip.path == from);
+            match &mut tab.kind {
+                TabKind::Directory { directory } => {
+                    let keeps_file = to.parent() == Some(directory.as_path());
+                    match (existing, &relocated) {
+                        (Some(idx), Some(clip)) if keeps_file => tab.clips[idx] = clip.clone(),
+                        (Some(idx), _) => {
+                            tab.clips.remove(idx);
+                        }
+                        (None, Some(clip)) if keeps_file => tab.insert_clip(clip.clone()),
+                        (None, _) => {}
+                    }
+                }
+                TabKind::Collection { collection } => {
+                    for entry in collection.iter_mut().filter(|entry| *entry == from) {
+                        *entry = to.to_path_buf();
+                    }
+                    match (existing, &relocated) {
+                        (Some(idx), Some(clip)) => tab.clips[idx] = clip.clone(),
+                        (Some(idx), None) => {
+                            tab.clips.remove(idx);
+                        }
+                        (None, _) => {}
+                    }
+                }
+                TabKind::Smart { .. } => {}
+            }
+        }
+
+        if self.favorites.remove(from) {
+            self.favorites.insert(to.to_path_buf());
+        }
+        if self.hidden_clips.remove(from) {
+            self.hidden_clips.insert(to.to_path_buf());
+        }
+        if self.selected_clips.remove(from) {
+            self.selected_clips.insert(to.to_path_buf());
+        }
+        if let Some(settings) = self.clip_settings.remove(from) {
+            self.clip_settings.insert(to.to_path_buf(), settings);
+        }
+        if let Some(hash) = self.clip_hashes.remove(from) {
+            self.clip_hashes.insert(to.to_path_buf(), hash);
+        }
+        if let Some(stats) = self.play_stats.remove(from) {
+            self.play_stats.insert(to.to_path_buf(), stats);
+        }
+        for record in self.history.iter_mut().filter(|record| record.path == from) {
+            record.path = to.to_path_buf();
+        }
+    }
+
+    fn snapshot_clip(&self, path: &std::path::Path) -> ClipSnapshot {
+        let positions = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, tab)| {
+                let order = match &tab.kind {
+                    TabKind::Collection { collection } => collection,
+                    _ => &tab.manual_order,
+                };
+                order
+                    .iter()
+                    .position(|entry| entry == path)
+                    .map(|position| (idx, position))
+            })
+            .collect();
+
+        let variant_groups = self
+            .tabs
+            .iter()
+            .enumerate()
+            .flat_map(|(tab_idx, tab)| {
+                tab.variant_groups
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(group_idx, group)| {
+                        let position = group.clips.iter().position(|entry| entry == path)?;
+                        Some((tab_idx, group_idx, group.name.clone(), position))
+                    })
+            })
+            .collect();
+
+        ClipSnapshot {
+            favorite: self.favorites.contains(path),
+            settings: self.clip_settings.get(path).cloned(),
+            positions,
+            variant_groups,
+            hidden: self.hidden_clips.contains(path),
+        }
+    }
+
+    /// Puts a clip back into the tabs and lists it was part of before it was trashed.
+    fn restore_clip(&mut self, path: &std::path::Path, snapshot: ClipSnapshot) {
+        for (idx, position) in snapshot.positions {
+            if let Some(tab) = self.tabs.get_mut(idx) {
+                let order = match &mut tab.kind {
+                    TabKind::Collection { collection } => collection,
+                    _ => &mut tab.manual_order,
+                };
+                order.insert(position.min(order.len()), path.to_path_buf());
+            }
+        }
+
+        let clip = readable_clip(path);
+        for tab in self.tabs.iter_mut() {
+            let contains = match &tab.kind {
+                TabKind::Directory { directory } => path.parent() == Some(directory.as_path()),
+                TabKind::Collection { collection } => collection.iter().any(|entry| entry == path),
+                TabKind::Smart { .. } => false,
+            };
+            if let Some(clip) = clip.as_ref().filter(|_| contains) {
+                tab.insert_clip(clip.clone());
+            }
+        }
+
+        for (tab_idx, group_idx, name, position) in snapshot.variant_groups {
+            let Some(tab) = self.tabs.get_mut(tab_idx) else {
+                continue;
+            };
+            // the group is gone if the clip was its last take
+            match tab
+                .variant_groups
+                .iter_mut()
+                .find(|group| group.name == name)
+            {
+                Some(group) => {
+                    let position = position.min(group.clips.len());
+                    group.clips.insert(position, path.to_path_buf());
+                }
+                None => {
+                    let group = VariantGroup {
+                        name,
+                        clips: vec![path.to_path_buf()],
+                        shuffle: Shuffle::default(),
+                    };
+                    let group_idx = group_idx.min(tab.variant_groups.len());
+                    tab.variant_groups.insert(group_idx, group);
+                }
+            }
+        }
+
+        if snapshot.favorite {
+            self.favorites.insert(path.to_path_buf());
+        }
+        if snapshot.hidden {
+            self.hidden_clips.insert(path.to_path_buf());
+        }
+        if let Some(settings) = snapshot.settings {
+            self.clip_settings.insert(path.to_path_buf(), settings);
+        }
+    }
+
+    /// Removes every reference to a file that no longer exists, keeping only
+    /// its play statistics for the usage report.
+    pub fn forget_clip(&mut self, path: &std::path::Path) {
+        for tab in self.tabs.iter_mut() {
+            tab.clips.retain(|clip| clip.path != path);
+            tab.manual_order.retain(|entry| entry != path);
+            for group in tab.variant_groups.iter_mut() {
+                group.clips.retain(|entry| entry != path);
+            }
+            tab.variant_groups.retain(|group| !group.clips.is_empty());
+            if let TabKind::Collection { collection } = &mut tab.kind {
+                collection.retain(|entry| entry != path);
+            }
+        }
+
+        self.favorites.remove(path);
+        self.clip_settings.remove(path);
+        self.clip_hashes.remove(path);
+        self.selected_clips.remove(path);
+        self.hidden_clips.remove(path);
+
+        if let Some(groups) = &mut self.duplicate_groups {
+            for group in groups.iter_mut() {
+                group.retain(|entry| entry != path);
+            }
+            groups.retain(|group| group.len() > 1);
+        }
+    }
+
+    /// Returns the starred clips found in any tab, each path listed once.
+    pub fn favorite_clips(&self) -> Vec<(usize, &AudioClip)> {
+        let mut seen = BTreeSet::new();
+
+        self.all_clips()
+            .filter(|(_, clip)| self.is_favorite(clip) && seen.insert(&clip.path))
+            .collect()
+    }
+
+    /// Evaluates the query of a smart tab against the clips of every directory
+    /// tab, each path listed once.
+    pub fn smart_clips(&self, query: &str) -> Result<Vec<(usize, &AudioClip)>, ParseError> {
+        let query = Query::parse(query)?;
+        let now = history::now();
+        let mut seen = BTreeSet::new();
+
+        Ok(self
+            .all_clips()
+            .filter(|(idx, clip)| {
+                let tab = &self.tabs[*idx];
+                let context = QueryContext {
+                    favorites: &self.favorites,
+                    play_stats: &self.play_stats,
+                    clip_settings: &self.clip_settings,
+                    tab_name: &tab.name,
+                    now,
+                };
+
+                tab.directory().is_some()
+                    && query.matches(clip, &context)
+                    && seen.insert(&clip.path)
+            })
+            .collect())
+    }
+
+    /// Returns the clips shown for the current selection as `(tab index, clip)`
+    /// pairs, ignoring the search query.
+    pub fn visible_clips(&self) -> Vec<(usize, &AudioClip)> {
+        if self.favorites_selected {
+            return self.favorite_clips();
+        }
+
+        let Some(tab) = self.get_current_tab() else {
+            return vec![];
+        };
+
+        let mut clips = if let TabKind::Smart { query } = &tab.kind {
+            self.smart_clips(query).unwrap_or_default()
+        } else {
+            self.all_clips()
+                .filter(|(idx, _)| *idx == self.current_tab)
+                .collect()
+        };
+        sorting::sort_clips(&mut clips, tab.sort, &self.play_stats, tab.manual_order());
+
+        clips
+    }
+
+    /// Returns the clips matching the search query as `(tab index, clip)`
+    /// pairs, best match first.
+    pub fn search_results(&self) -> Vec<(usize, &AudioClip)> {
+        let candidates = if self.search_all_tabs {
+            self.all_clips().collect()
+        } else {
+            self.visible_clips()
+        };
+
+        let mut results: Vec<(i32, usize, &AudioClip)> = candidates
+            .into_iter()
+            .filter_map(|(idx, clip)| {
+                let tags = self
+                    .clip_settings
+                    .get(&clip.path)
+                    .map(|settings| &settings.tags);
+                search::score_clip(&self.search_query, &self.tabs[idx], clip, tags)
+                    .map(|score| (score, idx, clip))
+            })
+            .collect();
+        results.sort_by_key(|(score, ..)| std::cmp::Reverse(*score));
+
+        results
+            .into_iter()
+            .map(|(_, idx, clip)| (idx, clip))
+            .collect()
+    }
+
+    /// Returns the clips to list: the search results while searching, otherwise
+    /// the current selection, narrowed down to the active tag filter.
+    pub fn displayed_clips(&self) -> Vec<(usize, &AudioClip)> {
+        let mut clips = if self.is_searching() {
+            self.search_results()
+        } else {
+            let mut clips = self.visible_clips();
+            // takes of a variant group are played through the group's button
+            if let Some(tab) = self.get_current_tab().filter(|_| !self.favorites_selected) {
+                clips.retain(|(_, clip)| {
+                    !tab.variant_groups
+                        .iter()
+                        .any(|group| group.clips.contains(&clip.path))
+                });
+            }
+            clips
+        };
+
+        if !self.tag_filter.is_empty() {
+            clips.retain(|(_, clip)| {
+                self.clip_settings
+                    .get(&clip.path)
+                    .is_some_and(|settings| settings.tags.is_superset(&self.tag_filter))
+            });
+        }
+
+        clips
+    }
+
+    /// Picks the next clip of the random pad out of the clips currently shown.
+    pub fn random_clip(&mut self) -> Option<AudioClip> {
+        let paths: Vec<std::path::PathBuf> = self
+            .displayed_clips()
+            .into_iter()
+            .map(|(_, clip)| clip.path.clone())
+            .collect();
+        let candidates: Vec<&std::path::Path> = paths.iter().map(|path| path.as_path()).collect();
+
+        let shuffle = if self.favorites_selected {
+            &mut self.favorites_shuffle
+        } else {
+            &mut self.tabs.get_mut(self.current_tab)?.shuffle
+        };
+        let path = shuffle.next(&candidates)?;
+
+        self.displayed_clips()
+            .into_iter()
+            .find(|(_, clip)| clip.path == path)
+            .map(|(_, clip)| clip.clone())
+    }
+
+    /// Picks the next take of a variant group of the current tab.
+    pub fn variant_clip(&mut self, group: usize) -> Option<AudioClip> {
+        let tab = self.tabs.get_mut(self.current_tab)?;
+        let group = tab.variant_groups.get_mut(group)?;
+
+        let candidates: Vec<&std::path::Path> = group
+            .clips
+            .iter()
+            .filter(|path| tab.clips.iter().any(|clip| clip.path == **path))
+            .map(|path| path.as_path())
+            .collect();
+        let path = group.shuffle.next(&candidates)?;
+
+        tab.clips.iter().find(|clip| clip.path == path).cloned()
+    }
+
+    /// Turns the selected clips of the current tab into a variant group,
+    /// taking them out of any group they were part of.
+    pub fn group_selection_as_variants(&mut self) -> bool {
+        if self.favorites_selected {
+            return false;
+        }
+        let Some(tab) = self.tabs.get_mut(self.current_tab) else {
+            return false;
+        };
+
+        let paths: Vec<_> = tab
+            .clips
+            .iter()
+            .filter(|clip| self.selected_clips.contains(&clip.path))
+            .map(|clip| clip.path.clone())
+            .collect();
+        if paths.len() < 2 {
+            return false;
+        }
+
+        for group in tab.variant_groups.iter_mut() {
+            group.clips.retain(|path| !paths.contains(path));
+        }
+        tab.variant_groups.retain(|group| !group.clips.is_empty());
+        tab.variant_groups.push(VariantGroup::new(paths));
+        self.selected_clips.clear();
+
+        true
+    }
+
+    pub fn ungroup_variants(&mut self, group: usize) {
+        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            if group < tab.variant_groups.len() {
+                tab.variant_groups.remove(group);
+            }
+        }
+    }
+
+    /// Returns every user tag in use, for the filter chips.
+    pub fn all_tags(&self) -> BTreeSet<&str> {
+        self.clip_settings
+            .values()
+            .flat_map(|settings| settings.tags.iter().map(String::as_str))
+            .collect()
+    }
+
+    pub fn get_clip_settings(&self, path: &std::path::Path) -> Option<&ClipSettings> {
+        self.clip_settings.get(path)
+    }
+
+    pub fn toggle_clip_selected(&mut self, path: &std::path::Path) {
+        if !self.selected_clips.remove(path) {
+            self.selected_clips.insert(path.to_path_buf());
+        }
+    }
+
+    pub fn select_all_clips(&mut self) {
+        let paths: Vec<_> = self
+            .displayed_clips()
+            .into_iter()
+            .map(|(_, clip)| clip.path.clone())
+            .collect();
+        self.selected_clips.extend(paths);
+    }
+
+    /// Applies `edit` to the settings of every selected clip, dropping settings
+    /// that end up empty so the config does not collect stale entries.
+    fn edit_selected_settings(&mut self, edit: impl Fn(&mut ClipSettings)) {
+        for path in self.selected_clips.iter() {
+            let settings = self.clip_settings.entry(path.clone()).or_default();
+            edit(settings);
+        }
+        self.clip_settings
+            .retain(|_, settings| !settings.is_empty());
+    }
+
+    pub fn add_tag_to_selection(&mut self, tag: &str) {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() {
+            self.edit_selected_settings(|settings| {
+                settings.tags.insert(tag.clone());
+            });
+        }
+    }
+
+    pub fn remove_tag_from_selection(&mut self, tag: &str) {
+        let tag = tag.trim().to_lowercase();
+        self.edit_selected_settings(|settings| {
+            settings.tags.remove(&tag);
+        });
+
+        let used = self.all_tags().contains(tag.as_str());
+        if !used {
+            self.tag_filter.remove(&tag);
+        }
+    }
+
+    pub fn set_selection_color(&mut self, color: Option<ClipColor>) {
+        self.edit_selected_settings(|settings| settings.color = color);
+    }
+
+    pub fn toggle_tag_filter(&mut self, tag: &str) {
+        if !self.tag_filter.remove(tag) {
+            self.tag_filter.insert(tag.to_owned());
+        }
+    }
+
+    fn update_playbacks_volume(&mut self) {
+        let global_volume = self.get_global_volume();
+        for (_, playback) in self.active_playbacks.iter_mut() {
+            let volume =
+                playback_volume(global_volume, self.clip_settings.get(&playback.clip.path));
+            let _ = playback.handle.set_volume(volume, Tween::default());
+        }
+    }
+
+    fn update_playbacks_speed(&mut self) {
+        let speed = self.get_global_speed();
+        for (_, playback) in self.active_playbacks.iter_mut() {
+            let _ = playback
+                .handle
+                .set_playback_rate(PlaybackRate(speed), Tween::default());
+        }
+    }
+
+    pub fn update_playbacks(&mut self) {
+        let now = std::time::Instant::now();
+        let mut finished = vec![];
+
+        self.active_playbacks.retain(|_id, playback| {
+            if playback.handle.state() == PlaybackState::Playing {
+                playback.played += now - playback.last_update;
+            }
+            playback.last_update = now;
+
+            if playback.handle.state() == PlaybackState::Stopped {
+                finished.push((
+                    playback.clip.path.clone(),
+                    playback.started,
+                    playback.played.as_secs_f64(),
+                ));
+                false
+            } else {
+                true
+            }
+        });
+
+        for (path, started, played) in finished.iter() {
+            history::finish_play(
+                &mut self.play_stats,
+                &mut self.history,
+                path,
+                *started,
+                *played,
+            );
+        }
+        if !finished.is_empty() {
+            self.set_plays_unsaved();
+        }
+    }
+
+    pub fn stop_all_playbacks(&mut self) {
+        for (_, playback) in self.active_playbacks.iter_mut() {
+            let _ = playback.handle.stop(Tween::default());
+        }
+    }
+
+    /// Plays a clip, telling the user when its file can't be played.
+    pub fn start_playback(&mut self, clip: AudioClip) {
+        let sound_data = match StreamingSoundData::from_file(&clip.path) {
+            Ok(sound_data) => sound_data,
+            Err(error) => {
+                let text = format!("Could not play {}: {}", clip.path.display(), error);
+                self.notify(text, None);
+                return;
+            }
+        };
+
+        let Some(audio_manager) = self.audio_manager.as_mut() else {
+            return;
+        };
+        let mut sound_handle = match audio_manager.play(sound_data) {
+            Ok(sound_handle) => sound_handle,
+            Err(error) => {
+                let text = format!("Could not play {}: {}", clip.path.display(), error);
+                self.notify(text, None);
+                return;
+            }
+        };
+
+        sound_handle.set_playback_rate(self.get_global_speed(), Tween::default());
+
+        let volume = playback_volume(self.get_global_volume(), self.clip_settings.get(&clip.path));
+        sound_handle.set_volume(volume, Tween::default());
+
+        let started = history::record_play(&mut self.play_stats, &mut self.history, &clip.path);
+        self.set_plays_unsaved();
 
         let playback = AudioPlayback {
             clip,
             handle: sound_handle,
+            started,
+            played: std::time::Duration::ZERO,
+            last_update: std::time::Instant::now(),
+        };
+
+        self.active_playbacks.insert(self.next_id, playback);
+        self.next_id += 1;
+    }
+
+    /// Brings back a clip that was playing when the app was closed, paused
+    /// where it was.
+    fn restore_playback(&mut self, playing: &PlayingClip) {
+        if !playing.path.is_file() {
+            return;
+        }
+        let Some(clip) = readable_clip(&playing.path) else {
+            return;
+        };
+        let Ok(sound_data) = StreamingSoundData::from_file(&playing.path) else {
+            return;
+        };
+        let volume = playback_volume(
+            self.get_global_volume(),
+            self.clip_settings.get(&playing.path),
+        );
+        let sound_data = sound_data
+            .start_position(playing.position)
+            .volume(volume)
+            .playback_rate(self.get_global_speed());
+        let Some(Ok(mut sound_handle)) = self
+            .audio_manager
+            .as_mut()
+            .map(|manager| manager.play(sound_data))
+        else {
+            return;
+        };
+        sound_handle.pause(Tween {
+            duration: std::time::Duration::ZERO,
+            ..Default::default()
+        });
+
+        let playback = AudioPlayback {
+            clip,
+            handle: sound_handle,
+            started: playing.started,
+            played: std::time::Duration::from_secs_f64(playing.played.max(0.0)),
+            last_update: std::time::Instant::now(),
         };
 
         self.active_playbacks.insert(self.next_id, playback);
@@ -215,23 +2142,331 @@ impl AppState {
         self.current_tab = self.tabs.len() - 1;
     }
 
-    pub fn close_tab(&mut self, index: usize) {
-        self.tabs.remove(index);
-        self.current_tab = if self.tabs.is_empty() {
-            0
-        } else {
-            usize::max(0, usize::min(self.current_tab, self.tabs.len() - 1))
+    /// Closes a tab, keeping it around so closing it can be undone.
+    pub fn close_tab(&mut self, index: usize) {
+        let tab = self.tabs.remove(index);
+        self.closed_tab = Some((index, tab));
+        self.remap_tabs(|idx| match idx.cmp(&index) {
+            std::cmp::Ordering::Less => Some(idx),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(idx - 1),
+        });
+    }
+
+    /// Puts the last closed tab back where it was and selects it.
+    pub fn undo_close_tab(&mut self) {
+        let Some((index, tab)) = self.closed_tab.take() else {
+            return;
+        };
+
+        let index = index.min(self.tabs.len());
+        self.tabs.insert(index, tab);
+        self.remap_tabs(|idx| Some(if idx >= index { idx + 1 } else { idx }));
+        self.select_tab(index);
+    }
+
+    /// Inserts a copy of a tab right after it and selects the copy.
+    pub fn duplicate_tab(&mut self, index: usize) {
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+
+        let mut copy = tab.clone();
+        copy.name = format!("{} copy", tab.name);
+        copy.id = next_tab_id();
+        self.tabs.insert(index + 1, copy);
+        self.remap_tabs(|idx| Some(if idx > index { idx + 1 } else { idx }));
+        self.select_tab(index + 1);
+    }
+
+    /// Adds the tabs of an unpacked board pack after the others, along with
+    /// the settings of its clips, and returns the index of the first one.
+    pub fn add_board(&mut self, board: Board) -> usize {
+        let first = self.tabs.len();
+        self.tabs.extend(board.tabs);
+        self.clip_settings.extend(board.clip_settings);
+
+        first
+    }
+
+    /// Moves the tab at `from` to the place of the tab at `to`, returning
+    /// whether anything moved.
+    pub fn move_tab(&mut self, from: usize, to: usize) -> bool {
+        if from == to || from >= self.tabs.len() || to >= self.tabs.len() {
+            return false;
+        }
+
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        self.remap_tabs(|idx| {
+            Some(if idx == from {
+                to
+            } else if from < idx && idx <= to {
+                idx - 1
+            } else if to <= idx && idx < from {
+                idx + 1
+            } else {
+                idx
+            })
+        });
+
+        true
+    }
+
+    /// Drops the dragged tab onto the hovered one, returning whether anything moved.
+    pub fn finish_tab_drag(&mut self) -> bool {
+        let (Some(from), Some(to)) = (self.tab_dragging.take(), self.tab_drag_target.take()) else {
+            return false;
+        };
+
+        self.move_tab(from, to)
+    }
+
+    /// Applies the name entered for the tab being renamed, ignoring blank ones.
+    pub fn submit_tab_rename(&mut self) -> bool {
+        let Some((index, name)) = self.tab_rename.take() else {
+            return false;
         };
+
+        let name = name.trim();
+        match self.tabs.get_mut(index) {
+            Some(tab) if !name.is_empty() => {
+                tab.name = name.to_owned();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Keeps everything that refers to tabs by index pointing at the same tabs
+    /// after they were added, removed or reordered. `map` returns the new
+    /// index of a tab, or `None` if it is gone.
+    fn remap_tabs(&mut self, map: impl Fn(usize) -> Option<usize>) {
+        self.current_tab = map(self.current_tab)
+            .unwrap_or(self.current_tab)
+            .min(self.tabs.len().saturating_sub(1));
+
+        self.file_edit = self.file_edit.take().and_then(|edit| match edit {
+            FileEdit::Move { path, tab } => map(tab).map(|tab| FileEdit::Move { path, tab }),
+            edit => Some(edit),
+        });
+        self.last_import = self.last_import.take().and_then(|summary| {
+            Some(ImportSummary {
+                tab: map(summary.tab)?,
+                ..summary
+            })
+        });
+        if let Some(FileOperation::Trashed { snapshot, .. }) = &mut self.last_file_operation {
+            snapshot.positions = snapshot
+                .positions
+                .iter()
+                .filter_map(|&(tab, position)| Some((map(tab)?, position)))
+                .collect();
+        }
+        self.tab_rename = self
+            .tab_rename
+            .take()
+            .and_then(|(tab, name)| Some((map(tab)?, name)));
     }
 
     pub fn select_tab(&mut self, index: usize) {
         self.current_tab = index;
+        self.favorites_selected = false;
+    }
+
+    pub fn select_favorites(&mut self) {
+        self.favorites_selected = true;
+        self.load_missing_clips(); // TODO: move to async
+    }
+
+    /// Indices of the tabs whose name contains the tab menu's query.
+    pub fn tab_menu_matches(&self) -> Vec<usize> {
+        let query = self.tab_menu_query.trim().to_lowercase();
+
+        self.tabs
+            .iter()
+            .enumerate()
+            .filter(|(_, tab)| tab.name.to_lowercase().contains(&query))
+            .map(|(idx, _)| idx)
+            .collect()
     }
 
     pub fn get_current_tab(&self) -> Option<&Tab> {
         self.tabs.get(self.current_tab)
     }
 
+    pub fn set_sort_mode(&mut self, mode: SortMode) {
+        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.sort = mode;
+        }
+    }
+
+    /// Whether clips of the current view can be dragged into a new order.
+    pub fn can_reorder(&self) -> bool {
+        !self.favorites_selected
+            && !self.is_searching()
+            && self.tag_filter.is_empty()
+            && self
+                .get_current_tab()
+                .is_some_and(|tab| tab.sort == SortMode::Manual && !tab.is_smart())
+    }
+
+    /// Drops the dragged clip onto the hovered one, returning whether anything moved.
+    pub fn finish_drag(&mut self) -> bool {
+        let (Some(from), Some(to)) = (self.dragging.take(), self.drag_target.take()) else {
+            return false;
+        };
+
+        if from == to {
+            return false;
+        }
+
+        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.move_clip(&from, &to);
+        }
+
+        true
+    }
+
+    pub fn set_layout(&mut self, layout: ClipLayout) {
+        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.layout = layout;
+        }
+    }
+
+    pub fn set_grid_columns(&mut self, columns: u16) {
+        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.grid_columns = columns.max(1);
+        }
+    }
+
+    /// The label shown for a clip, preferring one set by the user.
+    pub fn clip_label<'a>(&'a self, clip: &'a AudioClip, use_metadata: bool) -> &'a str {
+        self.get_clip_settings(&clip.path)
+            .and_then(|settings| settings.label.as_deref())
+            .unwrap_or_else(|| clip.display_name(use_metadata))
+    }
+
+    /// Sets or, when `label` is blank, clears the label of a clip.
+    pub fn set_clip_label(&mut self, path: &std::path::Path, label: &str) {
+        let label = label.trim();
+        let settings = self.clip_settings.entry(path.to_path_buf()).or_default();
+        settings.label = (!label.is_empty()).then(|| label.to_owned());
+
+        if settings.is_empty() {
+            self.clip_settings.remove(path);
+        }
+    }
+
+    /// Returns how far along the most advanced playback of a clip is, from 0 to 1.
+    pub fn playback_progress(&self, path: &std::path::Path) -> Option<f32> {
+        self.active_playbacks
+            .values()
+            .filter(|playback| playback.clip.path == path)
+            .map(|playback| {
+                let duration = playback.clip.duration.as_secs_f64();
+                if duration > 0.0 {
+                    (playback.handle.position() / duration).clamp(0.0, 1.0) as f32
+                } else {
+                    0.0
+                }
+            })
+            .reduce(f32::max)
+    }
+
+    /// The clips played most recently, newest first, paired with the loaded clip
+    /// if the file is still part of a tab.
+    pub fn recent_plays(&self, limit: usize) -> Vec<(&PlayRecord, Option<&AudioClip>)> {
+        self.history
+            .iter()
+            .rev()
+            .take(limit)
+            .map(|record| {
+                let clip = self
+                    .all_clips()
+                    .map(|(_, clip)| clip)
+                    .find(|clip| clip.path == record.path);
+                (record, clip)
+            })
+            .collect()
+    }
+
+    /// Lists every known clip with its usage, most played first, so unused
+    /// clips end up at the bottom of the report.
+    pub fn stats_report(&self) -> Vec<ReportEntry> {
+        let mut entries: Vec<ReportEntry> = vec![];
+
+        for (tab_idx, clip) in self.all_clips() {
+            if entries.iter().any(|entry| entry.path == clip.path) {
+                continue;
+            }
+
+            let stats = self.play_stats.get(&clip.path).cloned().unwrap_or_default();
+            entries.push(ReportEntry {
+                name: clip.name.clone(),
+                tab: self.tabs[tab_idx].name.clone(),
+                path: clip.path.clone(),
+                play_count: stats.play_count,
+                time_played: stats.time_played,
+                last_played: stats.last_played.map(history::format_timestamp),
+            });
+        }
+
+        // clips that were played but are no longer part of any tab
+        for (path, stats) in self.play_stats.iter() {
+            if entries.iter().any(|entry| entry.path == *path) {
+                continue;
+            }
+
+            entries.push(ReportEntry {
+                name: file_name(path),
+                tab: String::new(),
+                path: path.clone(),
+                play_count: stats.play_count,
+                time_played: stats.time_played,
+                last_played: stats.last_played.map(history::format_timestamp),
+            });
+        }
+
+        entries.sort_by(|a, b| {
+            b.play_count
+                .cmp(&a.play_count)
+                .then_with(|| sorting::natural_cmp(&a.name, &b.name))
+        });
+
+        entries
+    }
+
+    pub fn play_count(&self, path: &std::path::Path) -> u32 {
+        self.play_stats
+            .get(path)
+            .map_or(0, |stats| stats.play_count)
+    }
+
+    /// Adds the outcome of importing dropped files to the summary shown to the
+    /// user, starting over when they went into a different tab.
+    pub fn record_import(&mut self, tab: usize, count: usize, errors: &[String]) {
+        match &mut self.last_import {
+            Some(summary) if summary.tab == tab => {
+                summary.count += count;
+                summary.errors.extend_from_slice(errors);
+            }
+            _ => {
+                self.last_import = Some(ImportSummary {
+                    tab,
+                    count,
+                    errors: errors.to_vec(),
+                })
+            }
+        }
+    }
+
+    pub fn toggle_metadata(&mut self) {
+        if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+            tab.show_metadata = !tab.show_metadata;
+        }
+    }
+
     pub fn get_global_speed(&self) -> f64 {
         if self.speed_enabled {
             self.global_speed
@@ -240,30 +2475,186 @@ impl AppState {
         }
     }
 
-    pub fn set_dirty(&self) -> bool {
-        self.dirty
+    pub fn set_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Marks the play history as changed, to be saved with the next save or
+    /// on its own after [`HISTORY_SAVE_DELAY`].
+    pub fn set_plays_unsaved(&mut self) {
+        self.unsaved_plays_since
+            .get_or_insert_with(std::time::Instant::now);
+    }
+
+    /// Returns what the board is like now, to be brought back by undo or redo.
+    pub fn board_state(&self) -> BoardState {
+        BoardState {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| Tab {
+                    name: tab.name.clone(),
+                    kind: tab.kind.clone(),
+                    show_metadata: tab.show_metadata,
+                    sort: tab.sort,
+                    manual_order: tab.manual_order.clone(),
+                    layout: tab.layout,
+                    grid_columns: tab.grid_columns,
+                    variant_groups: tab.variant_groups.clone(),
+                    scroll: tab.scroll,
+                    clips: vec![],
+                    shuffle: Shuffle::default(),
+                    id: tab.id,
+                })
+                .collect(),
+            current_tab: self.current_tab,
+            favorites: self.favorites.clone(),
+            clip_settings: self.clip_settings.clone(),
+            hidden_clips: self.hidden_clips.clone(),
+            import_mode: self.import_mode,
+            volume_enabled: self.volume_enabled,
+            global_volume: self.global_volume,
+            global_speed: self.global_speed,
+            speed_enabled: self.speed_enabled,
+        }
+    }
+
+    /// Keeps what the board was like before `message` so it can be undone,
+    /// if the message changed it. A series of continuous messages counts as
+    /// one edit.
+    pub fn record_edit(&mut self, before: BoardState, message: &Message) {
+        if before == self.board_state() {
+            return;
+        }
+
+        let series = message.edit_series();
+        if series.is_none() || series != self.last_edit {
+            self.undo_stack.push(before);
+            if self.undo_stack.len() > UNDO_LIMIT {
+                self.undo_stack.remove(0);
+            }
+        }
+        self.last_edit = series;
+        self.redo_stack.clear();
+    }
+
+    /// Makes the next continuous edit a step of its own if `message`
+    /// finished the series before it.
+    pub fn end_edit_series(&mut self, message: &Message) {
+        if message.ends_edit_series() {
+            self.last_edit = None;
+        }
+    }
+
+    /// Reverts the last edit, returning whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let Some(board) = self.undo_stack.pop() else {
+            return false;
+        };
+
+        let current = self.board_state();
+        self.restore_board_state(board);
+        self.redo_stack.push(current);
+        true
+    }
+
+    /// Makes the last undone edit again, returning whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let Some(board) = self.redo_stack.pop() else {
+            return false;
+        };
+
+        let current = self.board_state();
+        self.restore_board_state(board);
+        self.undo_stack.push(current);
+        true
+    }
+
+    fn restore_board_state(&mut self, board: BoardState) {
+        // tabs that are still there keep their clips and where they were scrolled to
+        let mut old: Vec<Option<Tab>> = std::mem::take(&mut self.tabs)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut moved = BTreeMap::new();
+        for mut tab in board.tabs {
+            let found = old
+                .iter()
+                .position(|old| old.as_ref().is_some_and(|old| old.id == tab.id));
+            if let Some((idx, old)) = found.and_then(|idx| Some((idx, old[idx].take()?))) {
+                moved.insert(idx, self.tabs.len());
+                if old.kind == tab.kind {
+                    tab.clips = old.clips;
+                    tab.shuffle = old.shuffle;
+                    tab.scroll = old.scroll;
+                }
+            }
+            self.tabs.push(tab);
+        }
+        self.remap_tabs(|idx| moved.get(&idx).copied());
+
+        self.current_tab = board.current_tab;
+        self.favorites = board.favorites;
+        self.clip_settings = board.clip_settings;
+        self.hidden_clips = board.hidden_clips;
+        self.import_mode = board.import_mode;
+        self.volume_enabled = board.volume_enabled;
+        self.global_volume = board.global_volume;
+        self.update_playbacks_volume();
+        self.global_speed = board.global_speed;
+        self.speed_enabled = board.speed_enabled;
+        self.update_playbacks_speed();
+
+        // what was closed or being edited may be gone or back now
+        self.closed_tab = None;
+        self.label_edit = None;
+        self.last_edit = None;
+
+        if self.favorites_selected || self.get_current_tab().is_some_and(Tab::is_smart) {
+            self.load_missing_clips(); // TODO: move to async
+        } else if self
+            .get_current_tab()
+            .is_some_and(|tab| tab.clips.is_empty())
+        {
+            self.refresh_clips(); // TODO: move to async
+        }
     }
 }
 
 impl Application for SoundboardApp {
     type Executor = executor::Default;
-    type Flags = ();
+    type Flags = Flags;
     type Message = Message;
     type Theme = theme::Theme;
 
-    fn new(_flags: ()) -> (Self, Command<Message>) {
+    fn new(flags: Flags) -> (Self, Command<Message>) {
         let mut commands: Vec<Command<Message>> = vec![FONT_BYTES_REGULAR, FONT_BYTES_BOLD]
             .iter()
             .map(|&bytes| font::load(std::borrow::Cow::from(bytes)).map(Message::FontLoaded))
             .collect();
 
-        commands.push(Command::perform(SavedState::load(), Message::Loaded));
+        // clips trashed in an earlier run can't be brought back with undo
+        files::release_trash(None);
+
+        let profile = flags
+            .profile
+            .filter(|_| saving::profiles_enabled())
+            .unwrap_or_else(saving::last_profile);
+        commands.push(Command::perform(
+            SavedState::load(profile.clone()),
+            move |result| Message::Loaded(profile, result),
+        ));
 
         (SoundboardApp::Loading, Command::batch(commands))
     }
 
     fn title(&self) -> String {
-        TITLE.into()
+        match self {
+            SoundboardApp::Loaded(state) if state.profile != saving::DEFAULT_PROFILE => {
+                format!("{} - {}", TITLE, state.profile)
+            }
+            _ => TITLE.into(),
+        }
     }
 
     fn theme(&self) -> Self::Theme {
@@ -283,44 +2674,78 @@ impl Application for SoundboardApp {
         match self {
             SoundboardApp::Loading => match message {
                 // if loaded with saved state, set state
-                Message::Loaded(Ok(state)) => {
+                Message::Loaded(profile, Ok(state)) => {
                     let audio_manager =
                         AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())
                             .unwrap();
 
-                    let mut app_state = AppState {
-                        tabs: state.tabs.clone(),
-                        current_tab: state.current_tab,
-                        audio_manager: Some(audio_manager),
-                        global_volume: state.global_volume,
-                        global_speed: state.global_speed,
-                        ..Default::default()
-                    };
-                    app_state.refresh_clips(); // TODO: move to async
+                    saving::remember_profile(&profile);
+                    let mut state = AppState::from_saved(profile, state, Some(audio_manager));
+                    state.load_settings();
 
-                    *self = SoundboardApp::Loaded(app_state);
+                    // put the window back where it was left
+                    let mut commands = vec![
+                        window::resize(window::Id::MAIN, state.window_size),
+                        scrollable::snap_to(
+                            crate::ui::clips_scrollable_id(),
+                            scrollable::RelativeOffset {
+                                x: 0.0,
+                                y: state.scroll(),
+                            },
+                        ),
+                    ];
+                    if let Some(position) = state.window_position {
+                        commands.push(window::move_to(window::Id::MAIN, position));
+                    }
+                    *self = SoundboardApp::Loaded(state);
 
-                    Command::none()
+                    Command::batch(commands)
                 }
                 // if loaded with error or no state, set default state
-                Message::Loaded(Err(_)) => {
+                Message::Loaded(profile, Err(error)) => {
                     let audio_manager =
                         AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())
                             .unwrap();
 
-                    *self = SoundboardApp::Loaded(AppState {
+                    saving::remember_profile(&profile);
+                    let mut app_state = AppState {
+                        profile,
                         audio_manager: Some(audio_manager),
+                        can_save: error.can_overwrite(),
                         ..Default::default()
-                    });
+                    };
+                    if !matches!(error, LoadError::NotFound) {
+                        app_state.notify_load_error(&error);
+                    }
+
+                    app_state.load_settings();
+                    *self = SoundboardApp::Loaded(app_state);
 
                     Command::none()
                 }
+                Message::CloseRequested => window::close(window::Id::MAIN),
                 _ => Command::none(),
             },
-            SoundboardApp::Loaded(state) => Command::batch(vec![
-                crate::ui::update(state, &message),
-                crate::audio::update(state, &message),
-            ]),
+            SoundboardApp::Loaded(state) => {
+                let message = match message {
+                    Message::KeyPressed(key) => {
+                        state.key_message(&key).unwrap_or(Message::KeyPressed(key))
+                    }
+                    message => message,
+                };
+
+                let before = message.is_edit().then(|| state.board_state());
+                let command = Command::batch(vec![
+                    crate::ui::update(state, &message),
+                    crate::audio::update(state, &message),
+                ]);
+                if let Some(before) = before {
+                    state.record_edit(before, &message);
+                }
+                state.end_edit_series(&message);
+
+                command
+            }
         }
     }
 
@@ -328,52 +2753,501 @@ impl Application for SoundboardApp {
         let update_timer =
             time::every(std::time::Duration::from_millis(100)).map(|_| Message::UpdatePlaybacks);
 
-        Subscription::from(update_timer)
+        let key_presses = keyboard::on_key_press(key_press_message);
+
+        let settings_check =
+            time::every(std::time::Duration::from_secs(1)).map(|_| Message::CheckSettings);
+
+        let events = event::listen_with(|event, _status| match event {
+            Event::Window(_, window::Event::FileHovered(_)) => Some(Message::FilesHovered),
+            Event::Window(_, window::Event::FilesHoveredLeft) => Some(Message::FilesHoveredLeft),
+            Event::Window(_, window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+            Event::Window(_, window::Event::Resized { width, height }) => Some(
+                Message::WindowResized(iced::Size::new(width as f32, height as f32)),
+            ),
+            Event::Window(_, window::Event::Moved { x, y }) => {
+                Some(Message::WindowMoved(iced::Point::new(x as f32, y as f32)))
+            }
+            Event::Window(_, window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        });
+
+        let mut subscriptions = vec![update_timer, key_presses, settings_check, events];
+
+        // a drag ends wherever the button is let go, which only matters during one
+        let dragging = match self {
+            SoundboardApp::Loaded(state) => {
+                state.dragging.is_some() || state.tab_dragging.is_some()
+            }
+            SoundboardApp::Loading => false,
+        };
+        if dragging {
+            subscriptions.push(event::listen_with(|event, _status| match event {
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    Some(Message::DragEnd)
+                }
+                _ => None,
+            }));
+        }
+
+        Subscription::batch(subscriptions)
+    }
+}
+
+/// Returns the volume in decibels to play a clip at, scaling the global
+/// volume by the clip's own.
+fn playback_volume(global_volume: f32, settings: Option<&ClipSettings>) -> f32 {
+    let clip_volume = settings.and_then(|settings| settings.volume).unwrap_or(1.0);
+
+    (global_volume * clip_volume).log10() * 20.0
+}
+
+fn key_press_message(key: Key, modifiers: Modifiers) -> Option<Message> {
+    hotkey_name(&key, modifiers).map(Message::KeyPressed)
+}
+
+/// Names a key combination the way key bindings and clip hotkeys are
+/// written, like "Ctrl+Shift+K".
+fn hotkey_name(key: &Key, modifiers: Modifiers) -> Option<String> {
+    let name = match key.as_ref() {
+        Key::Character(c) => c.to_uppercase(),
+        Key::Named(
+            key::Named::Control | key::Named::Alt | key::Named::Shift | key::Named::Super,
+        ) => return None,
+        Key::Named(named) => format!("{:?}", named),
+        Key::Unidentified => return None,
+    };
+
+    let mut parts = vec![];
+    if modifiers.control() {
+        parts.push("Ctrl");
+    }
+    if modifiers.alt() {
+        parts.push("Alt");
+    }
+    if modifiers.shift() {
+        parts.push("Shift");
     }
+    if modifiers.logo() {
+        parts.push("Super");
+    }
+    parts.push(&name);
+
+    Some(parts.join("+"))
 }
 
 fn load_audio_clips(path: std::path::PathBuf) -> Vec<AudioClip> {
     let mut clips = vec![];
-    let mut paths: Vec<std::path::PathBuf> = std::fs::read_dir(path)
-        .unwrap()
+    let entries = match std::fs::read_dir(&path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            println!("Could not read {}: {}", path.display(), error);
+            return clips;
+        }
+    };
+    let mut paths: Vec<std::path::PathBuf> = entries
         .filter_map(Result::ok)
-        .filter(|entry| entry.file_type().unwrap().is_file())
-        .filter(|entry| {
-            if let Some(ext) = entry.path().extension() {
-                let ext = ext.to_string_lossy().to_lowercase();
-                ext == "mp3" || ext == "wav" || ext == "flac" || ext == "ogg"
-            } else {
-                false
-            }
-        })
         .map(|entry| entry.path())
+        // follows links so linked imports show up too
+        .filter(|path| path.is_file())
+        .filter(|path| is_audio_file(path))
         .collect();
-    paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    paths.sort_by(|a, b| sorting::natural_cmp(&file_name(a), &file_name(b)));
 
-    for path in paths {
-        let name = path.file_name().unwrap().to_str().unwrap().to_owned();
-        let duration = get_audio_duration(&path);
+    clips.extend(paths.iter().filter_map(|path| readable_clip(path)));
 
-        clips.push(AudioClip {
-            name,
-            path,
-            duration,
-        });
+    clips
+}
+
+pub fn is_audio_file(path: &std::path::Path) -> bool {
+    if let Some(ext) = path.extension() {
+        let ext = ext.to_string_lossy().to_lowercase();
+        ext == "mp3" || ext == "wav" || ext == "flac" || ext == "ogg"
+    } else {
+        false
     }
+}
 
-    clips
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Reads a clip, leaving it out with a message when the file isn't
+/// readable audio.
+fn readable_clip(path: &std::path::Path) -> Option<AudioClip> {
+    read_audio_clip(file_name(path), path.to_path_buf())
+        .map_err(|error| println!("Skipping {}: {}", path.display(), error))
+        .ok()
+}
+
+fn read_audio_clip(name: String, path: std::path::PathBuf) -> lofty::error::Result<AudioClip> {
+    use lofty::prelude::{Accessor, AudioFile, TaggedFileExt};
+
+    let tagged_file = lofty::probe::Probe::open(&path)?.read()?;
+
+    let duration = tagged_file.properties().duration();
+    let file_metadata = std::fs::metadata(&path).ok();
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag());
+
+    let read_text = |value: Option<std::borrow::Cow<str>>| {
+        value
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    Ok(AudioClip {
+        name,
+        path,
+        duration,
+        title: tag.and_then(|tag| read_text(tag.title())),
+        artist: tag.and_then(|tag| read_text(tag.artist())),
+        album: tag.and_then(|tag| read_text(tag.album())),
+        comment: tag.and_then(|tag| read_text(tag.comment())),
+        cover: tag.and_then(read_cover_art),
+        modified: file_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok()),
+        added: file_metadata.and_then(|metadata| metadata.created().or(metadata.modified()).ok()),
+    })
+}
+
+/// Wraps the first embedded picture in an SVG document so it can be drawn
+/// with the svg widget, which already handles raster images through resvg.
+fn read_cover_art(tag: &lofty::tag::Tag) -> Option<iced::widget::svg::Handle> {
+    use base64::Engine;
+    use lofty::picture::{MimeType, PictureType};
+
+    let picture = tag
+        .pictures()
+        .iter()
+        .find(|picture| picture.pic_type() == PictureType::CoverFront)
+        .or_else(|| tag.pictures().first())?;
+
+    let mime = match picture.mime_type() {
+        Some(MimeType::Png) => "image/png",
+        Some(MimeType::Jpeg) => "image/jpeg",
+        Some(MimeType::Gif) => "image/gif",
+        _ => return None,
+    };
+
+    let data = base64::engine::general_purpose::STANDARD.encode(picture.data());
+    let document = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 64 64"><image width="64" height="64" preserveAspectRatio="xMidYMid slice" xlink:href="data:{mime};base64,{data}"/></svg>"#
+    );
+
+    Some(iced::widget::svg::Handle::from_memory(
+        document.into_bytes(),
+    ))
 }
 
-fn get_audio_duration(path: &std::path::PathBuf) -> std::time::Duration {
-    use lofty::prelude::AudioFile;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_files_are_left_out() {
+        let dir = std::env::temp_dir().join(format!("soundboard-app-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let corrupt = dir.join("corrupt.mp3");
+        std::fs::write(&corrupt, "not audio").unwrap();
+
+        let mut collection = Tab::new_collection("Collection".to_owned());
+        collection.add_to_collection(&[corrupt, dir.join("missing.wav")]);
+        collection.load_clips();
+        assert!(collection.clips.is_empty());
+
+        let mut directory = Tab::new_directory(dir.clone());
+        directory.load_clips();
+        assert!(directory.clips.is_empty());
+
+        let mut gone = Tab::new_directory(dir.join("gone"));
+        gone.load_clips();
+        assert!(gone.clips.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn plays_are_saved_after_a_while() {
+        let mut state = AppState::default();
+
+        state.set_plays_unsaved();
+        assert!(state.has_unsaved_changes());
+        assert!(!state.should_save());
+
+        state.unsaved_plays_since = std::time::Instant::now().checked_sub(HISTORY_SAVE_DELAY);
+        assert!(state.should_save());
+
+        state.save();
+        assert!(!state.has_unsaved_changes());
+        state.set_dirty();
+        assert!(state.should_save());
+    }
+
+    #[test]
+    fn settings_with_a_missing_folder_apply() {
+        let mut state = AppState::default();
+        let mut settings = state.settings();
+        settings.volume = 0.3;
+        settings
+            .keys
+            .insert(Action::StopAll, vec!["Ctrl+S".to_owned()]);
+        settings.tabs = vec![
+            (
+                "Offline".to_owned(),
+                TabKind::Directory {
+                    directory: std::env::temp_dir().join("soundboard-app-no-such-folder"),
+                },
+            ),
+            (
+                "Picks".to_owned(),
+                TabKind::Collection { collection: vec![] },
+            ),
+        ];
+
+        assert!(state.apply_settings(settings.clone()));
+        assert_eq!(state.settings(), settings);
+        assert_eq!(state.global_volume, 0.3);
+        assert!(state.tabs[0].clips.is_empty());
+    }
+
+    fn audio_clip(path: &str) -> AudioClip {
+        let path = std::path::PathBuf::from(path);
+        AudioClip {
+            name: file_name(&path),
+            path,
+            duration: std::time::Duration::from_secs(1),
+            title: None,
+            artist: None,
+            album: None,
+            comment: None,
+            cover: None,
+            modified: None,
+            added: None,
+        }
+    }
+
+    #[test]
+    fn undone_trash_brings_back_groups_and_hiding() {
+        let laugh = |n: u32| std::path::PathBuf::from(format!("/sounds/laugh {n}.wav"));
+        let mut tab = Tab::new_directory("/sounds".into());
+        tab.variant_groups = vec![
+            VariantGroup::new(vec![laugh(1), laugh(2)]),
+            VariantGroup {
+                name: "solo".to_owned(),
+                clips: vec!["/sounds/solo.wav".into()],
+                shuffle: Shuffle::default(),
+            },
+        ];
+        let mut state = AppState {
+            tabs: vec![tab],
+            ..Default::default()
+        };
+        state.hidden_clips.insert(laugh(1));
+        let groups = state.tabs[0].variant_groups.clone();
+
+        for path in [laugh(1), "/sounds/solo.wav".into()] {
+            let snapshot = state.snapshot_clip(&path);
+            state.forget_clip(&path);
+            state.restore_clip(&path, snapshot);
+        }
+
+        let restored = &state.tabs[0].variant_groups;
+        assert_eq!(restored.len(), groups.len());
+        for (restored, group) in restored.iter().zip(&groups) {
+            assert_eq!(restored.name, group.name);
+            assert_eq!(restored.clips, group.clips);
+        }
+        assert!(state.hidden_clips.contains(&laugh(1)));
+    }
+
+    #[test]
+    fn inserted_clips_keep_the_loading_order() {
+        let mut directory = Tab::new_directory("/sounds".into());
+        for path in ["/sounds/b.wav", "/sounds/a10.wav", "/sounds/a2.wav"] {
+            directory.insert_clip(audio_clip(path));
+        }
+        let names: Vec<_> = directory
+            .clips
+            .iter()
+            .map(|clip| clip.name.as_str())
+            .collect();
+        assert_eq!(names, ["a2.wav", "a10.wav", "b.wav"]);
+
+        let mut collection = collection("Picks");
+        collection.kind = TabKind::Collection {
+            collection: vec!["/x/c.wav".into(), "/x/a.wav".into(), "/x/b.wav".into()],
+        };
+        for path in ["/x/b.wav", "/x/c.wav", "/x/a.wav"] {
+            collection.insert_clip(audio_clip(path));
+        }
+        let names: Vec<_> = collection
+            .clips
+            .iter()
+            .map(|clip| clip.name.as_str())
+            .collect();
+        assert_eq!(names, ["c.wav", "a.wav", "b.wav"]);
+    }
+
+    #[test]
+    fn settings_follow_files_renamed_outside_the_app() {
+        let dir =
+            std::env::temp_dir().join(format!("soundboard-app-{}-relink", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (old, new) = (dir.join("horn.wav"), dir.join("air horn.wav"));
+        crate::pack::tests::write_wav(&old, 800);
+
+        let mut state = AppState {
+            tabs: vec![Tab::new_directory(dir.clone())],
+            ..Default::default()
+        };
+        let settings = ClipSettings {
+            tags: BTreeSet::from(["loud".to_owned()]),
+            ..Default::default()
+        };
+        state.clip_settings.insert(old.clone(), settings.clone());
+        state.refresh_all_clips();
+        assert!(state.saved_state().clip_hashes.contains_key(&old));
+
+        std::fs::rename(&old, &new).unwrap();
+        state.refresh_all_clips();
+
+        assert_eq!(
+            state.clip_settings,
+            BTreeMap::from([(new.clone(), settings)])
+        );
+        assert_eq!(
+            state.saved_state().clip_hashes.keys().collect::<Vec<_>>(),
+            [&new]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn collection(name: &str) -> Tab {
+        Tab::new_collection(name.to_owned())
+    }
+
+    #[test]
+    fn undo_and_redo_edits() {
+        let mut state = AppState {
+            tabs: vec![collection("A"), collection("B")],
+            ..Default::default()
+        };
+
+        let before = state.board_state();
+        state.tabs.remove(0);
+        state.record_edit(before, &Message::CloseTab(0));
+
+        // messages that changed nothing leave no step behind
+        let before = state.board_state();
+        state.record_edit(before, &Message::ToggleMetadata);
+        assert_eq!(state.undo_stack.len(), 1);
+
+        assert!(state.undo());
+        assert_eq!(state.tabs.len(), 2);
+        assert_eq!(state.tabs[0].name, "A");
+        assert!(!state.undo());
+
+        assert!(state.redo());
+        assert_eq!(state.tabs.len(), 1);
+        assert_eq!(state.tabs[0].name, "B");
+        assert!(!state.redo());
+    }
+
+    #[test]
+    fn slider_drag_is_one_edit() {
+        let mut state = AppState::default();
+
+        for volume in [0.8, 0.6, 0.4] {
+            let before = state.board_state();
+            state.set_global_volume(volume);
+            state.record_edit(before, &Message::VolumeChanged(volume));
+        }
+        assert_eq!(state.undo_stack.len(), 1);
+
+        assert!(state.undo());
+        assert_eq!(state.global_volume, 1.0);
+    }
+
+    #[test]
+    fn separate_drags_are_separate_edits() {
+        let mut state = AppState::default();
 
-    let tagged_file = lofty::probe::Probe::open(path)
-        .expect("ERROR: Bad path provided!")
-        .read()
-        .expect("ERROR: Failed to read file!");
+        for drag in [[0.8, 0.6], [0.4, 0.2]] {
+            for volume in drag {
+                let before = state.board_state();
+                state.set_global_volume(volume);
+                state.record_edit(before, &Message::VolumeChanged(volume));
+            }
+            state.end_edit_series(&Message::SetDirty);
+        }
+        assert_eq!(state.undo_stack.len(), 2);
+
+        assert!(state.undo());
+        assert_eq!(state.global_volume, 0.6);
+        assert!(state.undo());
+        assert_eq!(state.global_volume, 1.0);
+    }
+
+    #[test]
+    fn typing_in_another_query_is_another_edit() {
+        let mut state = AppState {
+            tabs: vec![
+                Tab::new_smart("A".to_owned(), String::new()),
+                Tab::new_smart("B".to_owned(), String::new()),
+            ],
+            ..Default::default()
+        };
+
+        for (index, query) in [(0, "a"), (0, "ab"), (1, "b")] {
+            let before = state.board_state();
+            state.tabs[index].kind = TabKind::Smart {
+                query: query.to_owned(),
+            };
+            state.record_edit(before, &Message::SmartQueryChanged(index, query.to_owned()));
+        }
+        assert_eq!(state.undo_stack.len(), 2);
+    }
+
+    #[test]
+    fn undo_gives_tabs_their_own_clips() {
+        let mut state = AppState {
+            tabs: vec![collection("A"), collection("A")],
+            ..Default::default()
+        };
+        let (first, second) = (state.tabs[0].id, state.tabs[1].id);
+        state.tabs[1].scroll = 0.5;
+
+        let before = state.board_state();
+        state.tabs.remove(0);
+        state.record_edit(before, &Message::CloseTab(0));
+        assert!(state.undo());
 
-    let properties = tagged_file.properties();
-    let duration = properties.duration();
+        assert_eq!(state.tabs[0].id, first);
+        assert_eq!(state.tabs[0].scroll, 0.0);
+        assert_eq!(state.tabs[1].id, second);
+        assert_eq!(state.tabs[1].scroll, 0.5);
+    }
+
+    #[test]
+    fn new_edit_drops_redo() {
+        let mut state = AppState::default();
 
-    duration
+        let before = state.board_state();
+        state.tabs.push(collection("A"));
+        state.record_edit(before, &Message::NewCollection);
+        assert!(state.undo());
+        assert_eq!(state.redo_stack.len(), 1);
+
+        let before = state.board_state();
+        state.tabs.push(collection("B"));
+        state.record_edit(before, &Message::NewCollection);
+        assert!(state.redo_stack.is_empty());
+    }
 }
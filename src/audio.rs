@@ -1,9 +1,13 @@
-use iced::Command;
+use iced::{widget::svg, Command};
 use kira::{
     sound::{streaming::StreamingSoundHandle, FromFileError},
     Tween,
 };
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant, SystemTime},
+};
 
 use crate::app::{AppState, Message};
 
@@ -18,6 +22,11 @@ pub enum AudioCommand {
 pub struct AudioPlayback {
     pub clip: AudioClip,
     pub handle: StreamingSoundHandle<FromFileError>,
+    /// When the play was recorded in the history.
+    pub started: u64,
+    /// How long the clip has been audible so far.
+    pub played: Duration,
+    pub last_update: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -25,27 +34,127 @@ pub struct AudioClip {
     pub name: String,
     pub path: std::path::PathBuf,
     pub duration: Duration,
+
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub comment: Option<String>,
+    pub cover: Option<svg::Handle>,
+
+    pub modified: Option<SystemTime>,
+    /// When the file was created, which is usually when it was added to its folder.
+    pub added: Option<SystemTime>,
+}
+
+impl AudioClip {
+    /// Returns the tag title if requested and available, otherwise the file name.
+    pub fn display_name(&self, use_metadata: bool) -> &str {
+        match &self.title {
+            Some(title) if use_metadata => title,
+            _ => &self.name,
+        }
+    }
+
+    /// Returns "artist - album" built from whichever of the two tags are present.
+    pub fn subtitle(&self) -> Option<String> {
+        match (&self.artist, &self.album) {
+            (Some(artist), Some(album)) => Some(format!("{artist} - {album}")),
+            (Some(artist), None) => Some(artist.to_owned()),
+            (None, Some(album)) => Some(album.to_owned()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// User-assigned data for a clip, persisted by path.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClipSettings {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ClipColor>,
+    /// Replaces the file name on the clip's row or pad.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Scales the global volume for this clip, from 0 to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+    /// Key combination that plays the clip, like "Ctrl+Shift+K".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+}
+
+impl ClipSettings {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.color.is_none()
+            && self.label.is_none()
+            && self.volume.is_none()
+            && self.hotkey.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Teal,
+    Blue,
+    Purple,
+    Pink,
+}
+
+impl ClipColor {
+    pub const ALL: [ClipColor; 8] = [
+        ClipColor::Red,
+        ClipColor::Orange,
+        ClipColor::Yellow,
+        ClipColor::Green,
+        ClipColor::Teal,
+        ClipColor::Blue,
+        ClipColor::Purple,
+        ClipColor::Pink,
+    ];
+
+    pub fn color(self) -> iced::Color {
+        match self {
+            ClipColor::Red => iced::Color::from_rgb(0.88, 0.27, 0.27),
+            ClipColor::Orange => iced::Color::from_rgb(0.95, 0.55, 0.2),
+            ClipColor::Yellow => iced::Color::from_rgb(0.95, 0.82, 0.25),
+            ClipColor::Green => iced::Color::from_rgb(0.35, 0.75, 0.35),
+            ClipColor::Teal => iced::Color::from_rgb(0.2, 0.7, 0.7),
+            ClipColor::Blue => iced::Color::from_rgb(0.3, 0.5, 0.9),
+            ClipColor::Purple => iced::Color::from_rgb(0.6, 0.4, 0.85),
+            ClipColor::Pink => iced::Color::from_rgb(0.9, 0.45, 0.7),
+        }
+    }
 }
 
 pub fn update(state: &mut AppState, message: &Message) -> Command<Message> {
     match message {
         Message::VolumeToggled => {
             state.toggle_global_volume();
+            state.set_dirty();
         }
         Message::VolumeChanged(value) => {
             if !state.volume_enabled {
                 state.toggle_global_volume();
             }
             state.set_global_volume(*value);
+            state.set_dirty();
         }
         Message::SpeedToggled => {
             state.toggle_global_speed();
+            state.set_dirty();
         }
         Message::SpeedChanged(value) => {
             if !state.speed_enabled {
                 state.toggle_global_speed();
             }
             state.set_global_speed(*value);
+            state.set_dirty();
         }
         Message::AudioEvent(id, command) => {
             if let Some(playback) = state.active_playbacks.get_mut(&id) {
@@ -68,6 +177,21 @@ pub fn update(state: &mut AppState, message: &Message) -> Command<Message> {
         Message::StartPlayback(clip) => {
             state.start_playback(clip.to_owned());
         }
+        Message::PlayRandom => {
+            if let Some(clip) = state.random_clip() {
+                state.start_playback(clip);
+            }
+        }
+        Message::PlayVariant(group) => {
+            if let Some(clip) = state.variant_clip(*group) {
+                state.start_playback(clip);
+            }
+        }
+        Message::KeyPressed(hotkey) => {
+            if let Some(clip) = state.hotkey_clip(hotkey) {
+                state.start_playback(clip);
+            }
+        }
         Message::StopAllPlaybacks => {
             state.stop_all_playbacks();
         }
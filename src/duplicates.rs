@@ -0,0 +1,374 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+use symphonia::core::{
+    audio::SampleBuffer, codecs::DecoderOptions, errors::Error as DecodeError,
+    formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+};
+
+/// Length of one step of an audio fingerprint, in seconds.
+const WINDOW_SECONDS: f32 = 0.02;
+/// How many windows two fingerprints may be shifted against each other, to
+/// make up for encoder delay and padding.
+const MAX_SHIFT: usize = 10;
+/// Lowest correlation between two loudness envelopes that still counts as
+/// the same recording.
+const MIN_CORRELATION: f32 = 0.95;
+/// Fingerprints shorter than this are too generic to compare.
+const MIN_WINDOWS: usize = 25;
+
+/// Scans the given files in the background and returns every group of files
+/// that hold the same audio, each group ordered as in `paths`.
+pub async fn scan(paths: Vec<PathBuf>, compare_audio: bool) -> Vec<Vec<PathBuf>> {
+    async_std::task::spawn_blocking(move || find_duplicates(&paths, compare_audio)).await
+}
+
+/// Groups files with identical contents and, if `compare_audio` is set, files
+/// whose decoded audio sounds the same, catching re-encodes and format changes.
+pub fn find_duplicates(paths: &[PathBuf], compare_audio: bool) -> Vec<Vec<PathBuf>> {
+    let mut groups = Groups::new(paths.len());
+
+    let mut by_hash: BTreeMap<u64, usize> = BTreeMap::new();
+    for (idx, path) in paths.iter().enumerate() {
+        let Some(hash) = content_hash(path) else {
+            continue;
+        };
+
+        match by_hash.get(&hash) {
+            Some(&first) => groups.join(first, idx),
+            None => {
+                by_hash.insert(hash, idx);
+            }
+        }
+    }
+
+    if compare_audio {
+        let fingerprints: Vec<Option<Vec<f32>>> =
+            paths.iter().map(|path| fingerprint(path)).collect();
+
+        for a in 0..paths.len() {
+            for b in (a + 1)..paths.len() {
+                if groups.find(a) == groups.find(b) {
+                    continue;
+                }
+
+                if let (Some(fa), Some(fb)) = (&fingerprints[a], &fingerprints[b]) {
+                    if sounds_alike(fa, fb) {
+                        groups.join(a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut result: BTreeMap<usize, Vec<PathBuf>> = BTreeMap::new();
+    for (idx, path) in paths.iter().enumerate() {
+        result
+            .entry(groups.find(idx))
+            .or_default()
+            .push(path.clone());
+    }
+
+    result
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+/// Hashes a file a buffer at a time, so large files aren't read into memory
+/// all at once.
+pub fn content_hash(path: &std::path::Path) -> Option<u64> {
+    let mut reader = BufReader::new(std::fs::File::open(path).ok()?);
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+
+    loop {
+        let buffer = reader.fill_buf().ok()?;
+        if buffer.is_empty() {
+            break;
+        }
+        hasher.update(buffer);
+        let len = buffer.len();
+        reader.consume(len);
+    }
+
+    Some(hasher.digest())
+}
+
+/// Decodes a file into its loudness envelope, one RMS value per window.
+fn fingerprint(path: &std::path::Path) -> Option<Vec<f32>> {
+    let file = std::fs::File::open(path).ok()?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+    let mut format = probed.format;
+
+    let track = format.default_track()?;
+    let track_id = track.id;
+    let window_length = (track.codec_params.sample_rate? as f32 * WINDOW_SECONDS) as usize;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .ok()?;
+
+    let mut envelope = vec![];
+    let mut sum = 0.0;
+    let mut count = 0;
+
+    while let Ok(packet) = format.next_packet() {
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(_) => break,
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        for frame in buffer.samples().chunks(channels) {
+            let sample = frame.iter().sum::<f32>() / channels as f32;
+            sum += sample * sample;
+            count += 1;
+
+            if count == window_length {
+                envelope.push((sum / count as f32).sqrt());
+                sum = 0.0;
+                count = 0;
+            }
+        }
+    }
+
+    let is_silent = envelope.iter().all(|value| *value <= f32::EPSILON);
+    (!is_silent).then_some(envelope)
+}
+
+/// Whether two envelopes rise and fall together at some small shift, which
+/// ignores differences in overall volume.
+fn sounds_alike(a: &[f32], b: &[f32]) -> bool {
+    if a.len().min(b.len()) < MIN_WINDOWS || a.len().abs_diff(b.len()) > 2 * MAX_SHIFT {
+        return false;
+    }
+
+    (0..=MAX_SHIFT).any(|shift| {
+        correlation(&a[shift..], b) >= MIN_CORRELATION
+            || correlation(&b[shift..], a) >= MIN_CORRELATION
+    })
+}
+
+/// Pearson correlation of the overlapping part of two series.
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+
+    let mean_a = a.iter().sum::<f32>() / len as f32;
+    let mean_b = b.iter().sum::<f32>() / len as f32;
+
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        covariance += dx * dy;
+        variance_a += dx * dx;
+        variance_b += dy * dy;
+    }
+
+    let deviation = (variance_a * variance_b).sqrt();
+    if deviation <= f32::EPSILON {
+        return 0.0;
+    }
+
+    covariance / deviation
+}
+
+/// Union-find over file indices.
+struct Groups {
+    parents: Vec<usize>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, idx: usize) -> usize {
+        let parent = self.parents[idx];
+        if parent == idx {
+            return idx;
+        }
+
+        let root = self.find(parent);
+        self.parents[idx] = root;
+        root
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        // keep the earliest file as the root so groups stay in scan order
+        self.parents[a.max(b)] = a.min(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// A series that doesn't resemble itself at any shift.
+    fn noise(len: usize) -> Vec<f32> {
+        let mut state = 12345u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as f32 / 65536.0
+            })
+            .collect()
+    }
+
+    /// Writes a mono 16-bit 8 kHz WAV file with the given samples.
+    fn write_wav(path: &Path, samples: &[i16]) {
+        let data = samples.len() as u32 * 2;
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn groups_join_into_the_earliest() {
+        let mut groups = Groups::new(5);
+        groups.join(3, 1);
+        groups.join(4, 3);
+
+        assert_eq!(groups.find(4), 1);
+        assert_eq!(groups.find(3), 1);
+        assert_eq!(groups.find(0), 0);
+        assert_eq!(groups.find(2), 2);
+    }
+
+    #[test]
+    fn correlation_ignores_volume() {
+        let a = noise(50);
+        let louder: Vec<f32> = a.iter().map(|x| x * 3.0 + 1.0).collect();
+        let inverted: Vec<f32> = a.iter().map(|x| -x).collect();
+
+        assert!((correlation(&a, &louder) - 1.0).abs() < 1e-4);
+        assert!((correlation(&a, &inverted) + 1.0).abs() < 1e-4);
+        assert_eq!(correlation(&a, &[0.5; 50]), 0.0);
+    }
+
+    #[test]
+    fn alike_within_the_shift() {
+        let a = noise(100);
+
+        assert!(sounds_alike(&a, &a[MAX_SHIFT..]));
+        assert!(sounds_alike(&a[MAX_SHIFT..], &a));
+        assert!(!sounds_alike(&a, &a[MAX_SHIFT + 5..]));
+        assert!(!sounds_alike(
+            &a,
+            &noise(100).iter().rev().copied().collect::<Vec<_>>()
+        ));
+    }
+
+    #[test]
+    fn too_short_or_long_to_compare() {
+        let a = noise(100);
+
+        assert!(sounds_alike(&a[..MIN_WINDOWS], &a[..MIN_WINDOWS]));
+        assert!(!sounds_alike(&a[..MIN_WINDOWS - 1], &a[..MIN_WINDOWS - 1]));
+        assert!(!sounds_alike(&a, &a[..100 - 2 * MAX_SHIFT - 1]));
+    }
+
+    #[test]
+    fn finds_identical_and_alike_files() {
+        let dir =
+            std::env::temp_dir().join(format!("soundboard-duplicates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // a tone whose loudness changes every window
+        let envelope = noise(60);
+        let samples: Vec<i16> = (0..60 * 160)
+            .map(|i| {
+                let tone = if i % 16 < 8 { 1.0 } else { -1.0 };
+                (tone * envelope[i / 160] * 20000.0) as i16
+            })
+            .collect();
+        let quieter: Vec<i16> = samples.iter().map(|sample| sample / 2).collect();
+
+        let paths: Vec<PathBuf> = ["a.wav", "b.wav", "quiet.wav", "other.wav", "copy.txt"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        write_wav(&paths[0], &samples);
+        write_wav(&paths[1], &samples);
+        write_wav(&paths[2], &quieter);
+        write_wav(
+            &paths[3],
+            &noise(60 * 160)
+                .iter()
+                .map(|x| (x * 20000.0) as i16)
+                .collect::<Vec<_>>(),
+        );
+        std::fs::write(&paths[4], "not audio").unwrap();
+
+        assert_eq!(
+            find_duplicates(&paths, false),
+            [vec![paths[0].clone(), paths[1].clone()]]
+        );
+        assert_eq!(
+            find_duplicates(&paths, true),
+            [vec![paths[0].clone(), paths[1].clone(), paths[2].clone()]]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hash_reads_the_whole_file() {
+        let path = std::env::temp_dir().join(format!(
+            "soundboard-duplicates-hash-{}.bin",
+            std::process::id()
+        ));
+        let bytes: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &bytes).unwrap();
+
+        assert_eq!(
+            content_hash(&path),
+            Some(xxhash_rust::xxh3::xxh3_64(&bytes))
+        );
+        assert_eq!(content_hash(&path.with_extension("missing")), None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
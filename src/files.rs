@@ -0,0 +1,322 @@
+use crate::{app, saving};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Returns the folder a trashed clip waits in while trashing it can still be
+/// undone, before it goes to the system trash.
+pub fn trash_dir() -> PathBuf {
+    saving::data_dir().join("trash")
+}
+
+/// Renames a file inside its folder, keeping its extension unless `name`
+/// already ends in one for audio, and returns the new path.
+pub fn rename_file(path: &Path, name: &str) -> io::Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the name must not be empty or contain slashes",
+        ));
+    }
+
+    let mut target = path.with_file_name(name);
+    if !app::is_audio_file(&target) {
+        if let Some(extension) = path.extension() {
+            target = path.with_file_name(format!("{}.{}", name, extension.to_string_lossy()));
+        }
+    }
+
+    move_file_to(path, &target)?;
+    Ok(target)
+}
+
+/// Moves a file into another folder, keeping its name, and returns the new path.
+pub fn move_file(path: &Path, directory: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let target = directory.join(name);
+
+    move_file_to(path, &target)?;
+    Ok(target)
+}
+
+/// Moves a file into the trash folder and returns where it ended up. It
+/// keeps its name, so it shows up as itself once it is in the system trash.
+pub fn trash_file(path: &Path) -> io::Result<PathBuf> {
+    let directory = trash_dir().join(crate::history::now().to_string());
+    std::fs::create_dir_all(&directory)?;
+    let target = unique_path(&directory, path)?;
+
+    move_file_to(path, &target)?;
+    Ok(target)
+}
+
+/// Sends everything in the trash folder except `keep` on to the system
+/// trash, where it can be restored or emptied like any other deleted file.
+pub fn release_trash(keep: Option<&Path>) {
+    let paths_in = |directory: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(directory)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect()
+    };
+
+    for entry in paths_in(&trash_dir()) {
+        let files = if entry.is_dir() {
+            paths_in(&entry)
+        } else {
+            vec![entry.clone()]
+        };
+        for file in files.iter().filter(|file| Some(file.as_path()) != keep) {
+            match trash::delete(file) {
+                Ok(()) => println!("Moved {} to the system trash", file.display()),
+                Err(error) => println!(
+                    "Could not move {} to the system trash: {}",
+                    file.display(),
+                    error
+                ),
+            }
+        }
+        if entry.is_dir() {
+            // only goes once it is empty
+            let _ = std::fs::remove_dir(&entry);
+        }
+    }
+}
+
+/// Moves a file, refusing to replace an existing one and falling back to
+/// copying when the target is on another drive.
+pub fn move_file_to(from: &Path, to: &Path) -> io::Result<()> {
+    if from == to {
+        return Ok(());
+    }
+    if to.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+
+    match std::fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(error) = std::fs::copy(from, to) {
+                // don't leave half a copy behind
+                let _ = std::fs::remove_file(to);
+                return Err(error);
+            }
+            std::fs::remove_file(from)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// How dropped files end up in a tab's directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportMode {
+    #[default]
+    Copy,
+    /// Leaves the file where it is and adds a link to it instead.
+    Link,
+}
+
+/// Copies or links the given files, and the audio files inside the given
+/// folders, into `directory` in the background. Returns the imported paths
+/// and a message for every file that failed.
+pub async fn import(
+    paths: Vec<PathBuf>,
+    directory: PathBuf,
+    mode: ImportMode,
+) -> (Vec<PathBuf>, Vec<String>) {
+    async_std::task::spawn_blocking(move || {
+        let mut imported = vec![];
+        let mut errors = vec![];
+
+        for path in paths.iter().flat_map(|path| audio_files_in(path)) {
+            match import_file(&path, &directory, mode) {
+                Ok(target) => imported.push(target),
+                Err(error) => errors.push(format!("{}: {}", path.display(), error)),
+            }
+        }
+
+        (imported, errors)
+    })
+    .await
+}
+
+/// Returns `path` if it is an audio file, or every audio file below it if it
+/// is a folder.
+pub fn audio_files_in(path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    collect_audio_files(path, &mut BTreeSet::new(), &mut files);
+    files
+}
+
+/// Adds the audio files at or below `path` to `files`. Folders are looked
+/// into once, by their canonical path, so links that lead back up the tree
+/// don't loop.
+fn collect_audio_files(path: &Path, visited: &mut BTreeSet<PathBuf>, files: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        if app::is_audio_file(path) {
+            files.push(path.to_path_buf());
+        }
+        return;
+    }
+
+    let Ok(canonical) = path.canonicalize() else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for entry in entries {
+        collect_audio_files(&entry, visited, files);
+    }
+}
+
+/// Copies or links a file into `directory` under a name that is not taken yet.
+fn import_file(path: &Path, directory: &Path, mode: ImportMode) -> io::Result<PathBuf> {
+    let target = unique_path(directory, path)?;
+
+    match mode {
+        ImportMode::Copy => {
+            std::fs::copy(path, &target)?;
+        }
+        ImportMode::Link => link_file(&path.canonicalize()?, &target)?,
+    }
+
+    Ok(target)
+}
+
+#[cfg(unix)]
+fn link_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+/// Symbolic links need extra privileges on Windows, so a hard link is used.
+#[cfg(not(unix))]
+fn link_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::fs::hard_link(original, link)
+}
+
+/// Picks a path in `directory` for the file, numbering it like "clip (2).mp3"
+/// when the name is already taken.
+fn unique_path(directory: &Path, path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let mut target = directory.join(name);
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut counter = 2;
+    while target.exists() || target.is_symlink() {
+        target = directory.join(format!("{stem} ({counter}){extension}"));
+        counter += 1;
+    }
+
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty folder for one test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("soundboard-files-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn audio_files_in_nested_folders() {
+        let dir = temp_dir("nested");
+        std::fs::create_dir_all(dir.join("b/c")).unwrap();
+        for file in ["a.mp3", "notes.txt", "b/b.wav", "b/c/c.OGG"] {
+            std::fs::write(dir.join(file), []).unwrap();
+        }
+
+        assert_eq!(
+            audio_files_in(&dir),
+            vec![
+                dir.join("a.mp3"),
+                dir.join("b/b.wav"),
+                dir.join("b/c/c.OGG")
+            ]
+        );
+        assert_eq!(audio_files_in(&dir.join("a.mp3")), vec![dir.join("a.mp3")]);
+        assert!(audio_files_in(&dir.join("notes.txt")).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn move_refuses_to_replace() {
+        let dir = temp_dir("move");
+        std::fs::write(dir.join("a.mp3"), "a").unwrap();
+        std::fs::write(dir.join("b.mp3"), "b").unwrap();
+
+        let error = move_file_to(&dir.join("a.mp3"), &dir.join("b.mp3")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(dir.join("b.mp3")).unwrap(), b"b");
+
+        // a failed rename is not taken for another drive
+        let error = move_file_to(&dir.join("missing.mp3"), &dir.join("c.mp3")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(!dir.join("c.mp3").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rename_keeps_the_extension() {
+        let dir = temp_dir("rename");
+        std::fs::write(dir.join("clip.mp3"), []).unwrap();
+
+        let renamed = rename_file(&dir.join("clip.mp3"), " airhorn ").unwrap();
+        assert_eq!(renamed, dir.join("airhorn.mp3"));
+        assert!(rename_file(&renamed, "a/b").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn audio_files_in_link_loop() {
+        let dir = temp_dir("loop");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub/clip.mp3"), []).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub/up")).unwrap();
+
+        assert_eq!(audio_files_in(&dir), vec![dir.join("sub/clip.mp3")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
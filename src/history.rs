@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// How many plays are kept in the history before the oldest are dropped.
+pub const HISTORY_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipStats {
+    pub play_count: u32,
+    pub last_played: Option<u64>,
+    /// Total seconds the clip was heard, across all plays.
+    #[serde(default)]
+    pub time_played: f64,
+}
+
+pub type PlayStats = BTreeMap<PathBuf, ClipStats>;
+
+/// A single play of a clip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayRecord {
+    pub path: PathBuf,
+    /// When playback started, in seconds since the unix epoch.
+    pub started: u64,
+    /// Seconds the clip was heard before it stopped, filled in once it does.
+    #[serde(default)]
+    pub played: f64,
+}
+
+/// One line of the exported usage report.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    pub name: String,
+    pub tab: String,
+    pub path: PathBuf,
+    pub play_count: u32,
+    pub time_played: f64,
+    pub last_played: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// Counts a new play of `path` and appends it to the history, returning the
+/// time it was recorded at so the record can be finished later.
+pub fn record_play(
+    stats: &mut PlayStats,
+    history: &mut Vec<PlayRecord>,
+    path: &std::path::Path,
+) -> u64 {
+    let started = now();
+
+    let entry = stats.entry(path.to_path_buf()).or_default();
+    entry.play_count += 1;
+    entry.last_played = Some(started);
+
+    history.push(PlayRecord {
+        path: path.to_path_buf(),
+        started,
+        played: 0.0,
+    });
+    if history.len() > HISTORY_LIMIT {
+        history.drain(..history.len() - HISTORY_LIMIT);
+    }
+
+    started
+}
+
+/// Stores how long a play that started at `started` lasted.
+pub fn finish_play(
+    stats: &mut PlayStats,
+    history: &mut [PlayRecord],
+    path: &std::path::Path,
+    started: u64,
+    played: f64,
+) {
+    if let Some(entry) = stats.get_mut(path) {
+        entry.time_played += played;
+    }
+
+    let record = history
+        .iter_mut()
+        .rev()
+        .find(|record| record.path == path && record.started == started);
+    if let Some(record) = record {
+        record.played = played;
+    }
+}
+
+pub fn to_csv(entries: &[ReportEntry]) -> String {
+    let mut csv = String::from("name,tab,path,play_count,time_played,last_played\n");
+
+    for entry in entries {
+        let fields = [
+            csv_field(&entry.name),
+            csv_field(&entry.tab),
+            csv_field(&entry.path.to_string_lossy()),
+            entry.play_count.to_string(),
+            format!("{:.1}", entry.time_played),
+            entry.last_played.clone().unwrap_or_default(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Formats a unix timestamp as an ISO 8601 UTC date and time.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Describes how long ago a unix timestamp was, e.g. "5 min ago".
+pub fn format_elapsed(timestamp: u64) -> String {
+    let elapsed = now().saturating_sub(timestamp);
+
+    match elapsed {
+        0..=59 => "just now".to_owned(),
+        60..=3599 => format!("{} min ago", elapsed / 60),
+        3600..=86399 => format!("{} h ago", elapsed / 3600),
+        _ => format!("{} d ago", elapsed / 86400),
+    }
+}
+
+/// Returns the current time as seconds since the unix epoch.
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_utc_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1709251199), "2024-02-29T23:59:59Z");
+        assert_eq!(format_timestamp(1704067199), "2023-12-31T23:59:59Z");
+        assert_eq!(format_timestamp(1704067200), "2024-01-01T00:00:00Z");
+        // 2100 is not a leap year
+        assert_eq!(format_timestamp(4107542400), "2100-03-01T00:00:00Z");
+    }
+
+    #[test]
+    fn csv_quotes_what_needs_it() {
+        let entries = [
+            ReportEntry {
+                name: "Horn, loud".to_owned(),
+                tab: "Say \"hi\"".to_owned(),
+                path: PathBuf::from("/sounds/horn.wav"),
+                play_count: 3,
+                time_played: 4.31,
+                last_played: Some(format_timestamp(0)),
+            },
+            ReportEntry {
+                name: "plain".to_owned(),
+                tab: "Tab".to_owned(),
+                path: PathBuf::from("/sounds/plain.wav"),
+                play_count: 0,
+                time_played: 0.0,
+                last_played: None,
+            },
+        ];
+
+        assert_eq!(
+            to_csv(&entries),
+            "name,tab,path,play_count,time_played,last_played\n\
+             \"Horn, loud\",\"Say \"\"hi\"\"\",/sounds/horn.wav,3,4.3,1970-01-01T00:00:00Z\n\
+             plain,Tab,/sounds/plain.wav,0,0.0,\n"
+        );
+    }
+
+    #[test]
+    fn plays_are_counted_and_finished() {
+        let mut stats = PlayStats::new();
+        let mut history = vec![];
+        let path = std::path::Path::new("/sounds/horn.wav");
+
+        let started = record_play(&mut stats, &mut history, path);
+        finish_play(&mut stats, &mut history, path, started, 1.5);
+
+        assert_eq!(stats[path].play_count, 1);
+        assert_eq!(stats[path].last_played, Some(started));
+        assert_eq!(stats[path].time_played, 1.5);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].played, 1.5);
+    }
+}
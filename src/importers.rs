@@ -0,0 +1,503 @@
+//! Reading boards set up in other soundboard apps: Soundux-style JSON
+//! configs, M3U and PLS playlists, and folders with a subfolder per category.
+
+use crate::{
+    app::{self, Tab},
+    audio::ClipSettings,
+    pack::Board,
+    saving::FileError,
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Extensions of the files [`import`] understands.
+pub const EXTENSIONS: [&str; 4] = ["json", "m3u", "m3u8", "pls"];
+
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    File(FileError),
+    /// The file isn't in a layout any of the importers understand.
+    Format {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl ImportError {
+    fn format(path: &Path, message: impl ToString) -> Self {
+        Self::Format {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::File(error) => write!(f, "{}", error),
+            ImportError::Format { path, message } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+/// Reads another app's board from a config or playlist file, or from a
+/// folder whose subfolders become one tab each.
+pub async fn import(path: PathBuf) -> Result<Board, ImportError> {
+    if path.is_dir() {
+        return import_categories(path);
+    }
+
+    let contents = async_std::fs::read(&path)
+        .await
+        .map_err(|error| ImportError::File(FileError::new(&path, &error)))?;
+    let contents = String::from_utf8_lossy(&contents);
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let board = match extension.as_str() {
+        "json" => import_soundux(&path, &contents)?,
+        "m3u" | "m3u8" => import_m3u(&path, &contents),
+        "pls" => import_pls(&path, &contents),
+        _ => {
+            return Err(ImportError::format(
+                &path,
+                "not a supported board or playlist",
+            ))
+        }
+    };
+
+    if board.tabs.is_empty() {
+        return Err(ImportError::format(&path, "there are no sounds in it"));
+    }
+    Ok(board)
+}
+
+#[derive(Deserialize)]
+struct SounduxConfig {
+    #[serde(default)]
+    data: Option<SounduxData>,
+    #[serde(default)]
+    tabs: Vec<SounduxTab>,
+}
+
+#[derive(Deserialize)]
+struct SounduxData {
+    #[serde(default)]
+    tabs: Vec<SounduxTab>,
+}
+
+#[derive(Deserialize)]
+struct SounduxTab {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    path: PathBuf,
+    #[serde(default, alias = "clips")]
+    sounds: Vec<SounduxSound>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SounduxSound {
+    #[serde(default)]
+    name: String,
+    path: PathBuf,
+    /// Either a list of key codes, as Soundux writes them, or a name like
+    /// "Ctrl+K".
+    #[serde(default, alias = "hotkey")]
+    hotkeys: Value,
+    /// Volume in percent.
+    #[serde(default, alias = "volume")]
+    local_volume: Option<f32>,
+}
+
+/// Reads a Soundux config, or any JSON shaped like it: tabs with a name, a
+/// folder and a list of sounds.
+fn import_soundux(path: &Path, contents: &str) -> Result<Board, ImportError> {
+    let config: SounduxConfig = serde_json::from_str(contents)
+        .map_err(|error| ImportError::format(path, format!("not a Soundux config, {error}")))?;
+    let source_tabs = match config.data {
+        Some(data) if !data.tabs.is_empty() => data.tabs,
+        _ => config.tabs,
+    };
+
+    let mut tabs = vec![];
+    let mut clip_settings = BTreeMap::new();
+    for source in source_tabs {
+        let mut tab = if source.path.is_dir() {
+            Tab::new_directory(source.path.clone())
+        } else {
+            let mut tab = Tab::new_collection(String::new());
+            let paths: Vec<PathBuf> = source
+                .sounds
+                .iter()
+                .map(|sound| sound.path.clone())
+                .collect();
+            tab.add_to_collection(&paths);
+            tab
+        };
+        if !source.name.is_empty() {
+            tab.name = source.name;
+        }
+
+        for sound in source.sounds {
+            let settings = ClipSettings {
+                label: clip_label(&sound.path, &sound.name),
+                volume: sound
+                    .local_volume
+                    .map(|volume| (volume / 100.0).clamp(0.0, 1.0)),
+                hotkey: match &sound.hotkeys {
+                    Value::String(name) => Some(name.clone()).filter(|name| !name.is_empty()),
+                    Value::Array(codes) => hotkey_from_codes(codes),
+                    _ => None,
+                },
+                ..Default::default()
+            };
+            if !settings.is_empty() {
+                clip_settings.insert(sound.path, settings);
+            }
+        }
+        tabs.push(tab);
+    }
+
+    Ok(Board {
+        source: path.to_path_buf(),
+        tabs,
+        clip_settings,
+    })
+}
+
+/// Reads an M3U playlist into a collection, using `#EXTINF` titles as
+/// labels and `#PLAYLIST` as the tab name.
+fn import_m3u(path: &Path, contents: &str) -> Board {
+    let mut name = playlist_name(path);
+    let mut entries = vec![];
+    let mut title = None;
+
+    for line in contents.lines().map(str::trim) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            title = info
+                .split_once(',')
+                .map(|(_, title)| title.trim().to_owned())
+                .filter(|title| !title.is_empty());
+        } else if let Some(playlist) = line.strip_prefix("#PLAYLIST:") {
+            name = playlist.trim().to_owned();
+        } else if !line.is_empty() && !line.starts_with('#') {
+            if let Some(entry) = playlist_entry(path, line) {
+                entries.push((entry, title.take()));
+            }
+        }
+    }
+
+    playlist_board(path, name, entries)
+}
+
+/// Reads a PLS playlist into a collection, using the `TitleN` keys as labels.
+fn import_pls(path: &Path, contents: &str) -> Board {
+    let mut files = BTreeMap::new();
+    let mut titles = BTreeMap::new();
+
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_owned();
+
+        if let Some(number) = key.strip_prefix("file").and_then(|n| n.parse::<u32>().ok()) {
+            files.insert(number, value);
+        } else if let Some(number) = key
+            .strip_prefix("title")
+            .and_then(|n| n.parse::<u32>().ok())
+        {
+            titles.insert(number, value);
+        }
+    }
+
+    let entries = files
+        .into_iter()
+        .filter_map(|(number, file)| Some((playlist_entry(path, &file)?, titles.remove(&number))))
+        .collect();
+
+    playlist_board(path, playlist_name(path), entries)
+}
+
+fn playlist_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Resolves a playlist line against the playlist's folder, leaving out
+/// streams and anything that isn't audio.
+fn playlist_entry(playlist: &Path, entry: &str) -> Option<PathBuf> {
+    if entry.contains("://") && !entry.starts_with("file://") {
+        return None;
+    }
+
+    let entry = PathBuf::from(entry.trim_start_matches("file://"));
+    let entry = match playlist.parent() {
+        Some(folder) if entry.is_relative() => folder.join(entry),
+        _ => entry,
+    };
+
+    app::is_audio_file(&entry).then_some(entry)
+}
+
+fn playlist_board(path: &Path, name: String, entries: Vec<(PathBuf, Option<String>)>) -> Board {
+    let mut tab = Tab::new_collection(name);
+    let paths: Vec<PathBuf> = entries.iter().map(|(path, _)| path.clone()).collect();
+    tab.add_to_collection(&paths);
+
+    let clip_settings = entries
+        .into_iter()
+        .filter_map(|(path, title)| {
+            let label = clip_label(&path, &title?)?;
+            let settings = ClipSettings {
+                label: Some(label),
+                ..Default::default()
+            };
+            Some((path, settings))
+        })
+        .collect();
+
+    Board {
+        source: path.to_path_buf(),
+        tabs: if paths.is_empty() { vec![] } else { vec![tab] },
+        clip_settings,
+    }
+}
+
+/// Makes a tab of every subfolder with audio files in it, and of the folder
+/// itself if it has some too.
+fn import_categories(path: PathBuf) -> Result<Board, ImportError> {
+    let entries = std::fs::read_dir(&path)
+        .map_err(|error| ImportError::File(FileError::new(&path, &error)))?;
+
+    let mut folders: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    folders.sort_by(|a, b| crate::sorting::natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+    folders.insert(0, path.clone());
+
+    let tabs: Vec<Tab> = folders
+        .into_iter()
+        .filter(|folder| has_audio_files(folder))
+        .map(Tab::new_directory)
+        .collect();
+    if tabs.is_empty() {
+        return Err(ImportError::format(
+            &path,
+            "none of its folders have sounds in them",
+        ));
+    }
+
+    Ok(Board {
+        source: path,
+        tabs,
+        clip_settings: BTreeMap::new(),
+    })
+}
+
+fn has_audio_files(folder: &Path) -> bool {
+    std::fs::read_dir(folder)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .any(|entry| app::is_audio_file(&entry.path()) && entry.path().is_file())
+}
+
+/// Returns the name given to a clip when it differs from its file name.
+fn clip_label(path: &Path, name: &str) -> Option<String> {
+    let name = name.trim();
+    let file_name = path.file_name()?.to_string_lossy();
+    let stem = path.file_stem()?.to_string_lossy();
+
+    (!name.is_empty() && name != file_name && name != stem).then(|| name.to_owned())
+}
+
+/// Turns Soundux key codes into a hotkey name like "Ctrl+Shift+K". They are
+/// read as Windows virtual-key codes; a combination with any other code is
+/// left out.
+fn hotkey_from_codes(codes: &[Value]) -> Option<String> {
+    let mut modifiers = vec![];
+    let mut keys = vec![];
+
+    for code in codes {
+        let code = code.as_u64()?;
+        match code {
+            0x11 | 0xA2 | 0xA3 => modifiers.push("Ctrl"),
+            0x12 | 0xA4 | 0xA5 => modifiers.push("Alt"),
+            0x10 | 0xA0 | 0xA1 => modifiers.push("Shift"),
+            0x5B | 0x5C => modifiers.push("Super"),
+            0x30..=0x39 | 0x41..=0x5A => keys.push((code as u8 as char).to_string()),
+            0x60..=0x69 => keys.push(((b'0' + (code - 0x60) as u8) as char).to_string()),
+            0x70..=0x87 => keys.push(format!("F{}", code - 0x6F)),
+            0x20 => keys.push("Space".to_owned()),
+            0x0D => keys.push("Enter".to_owned()),
+            _ => return None,
+        }
+    }
+    if keys.len() != 1 {
+        return None;
+    }
+
+    // same order as the names of pressed keys, so they compare equal
+    let mut parts: Vec<&str> = ["Ctrl", "Alt", "Shift", "Super"]
+        .into_iter()
+        .filter(|modifier| modifiers.contains(modifier))
+        .collect();
+    parts.push(&keys[0]);
+
+    Some(parts.join("+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn label(board: &Board, path: &str) -> Option<String> {
+        board.clip_settings.get(Path::new(path))?.label.clone()
+    }
+
+    #[test]
+    fn m3u_titles_become_labels() {
+        let board = import_m3u(
+            Path::new("/lists/party.m3u"),
+            "#EXTM3U\n\
+             #PLAYLIST:Party\n\
+             #EXTINF:3,Air horn\n\
+             sounds/horn.mp3\n\
+             #EXTINF:2,drum\n\
+             /abs/drum.wav\n\
+             http://example.com/stream.mp3\n\
+             notes.txt\n\
+             file:///abs/bell.ogg\n",
+        );
+
+        assert_eq!(board.tabs.len(), 1);
+        assert_eq!(board.tabs[0].name, "Party");
+        assert_eq!(
+            board.tabs[0].manual_order(),
+            [
+                PathBuf::from("/lists/sounds/horn.mp3"),
+                PathBuf::from("/abs/drum.wav"),
+                PathBuf::from("/abs/bell.ogg"),
+            ]
+        );
+        assert_eq!(
+            label(&board, "/lists/sounds/horn.mp3").as_deref(),
+            Some("Air horn")
+        );
+        // the title matches the file name, so there's nothing to label
+        assert_eq!(label(&board, "/abs/drum.wav"), None);
+        assert_eq!(label(&board, "/abs/bell.ogg"), None);
+    }
+
+    #[test]
+    fn pls_pairs_files_with_titles_by_number() {
+        let board = import_pls(
+            Path::new("/lists/set.pls"),
+            "[playlist]\n\
+             File2=b.wav\n\
+             Title2=Second\n\
+             File1=a.wav\n\
+             NumberOfEntries=2\n",
+        );
+
+        assert_eq!(board.tabs[0].name, "set");
+        assert_eq!(
+            board.tabs[0].manual_order(),
+            [PathBuf::from("/lists/a.wav"), PathBuf::from("/lists/b.wav")]
+        );
+        assert_eq!(label(&board, "/lists/a.wav"), None);
+        assert_eq!(label(&board, "/lists/b.wav").as_deref(), Some("Second"));
+    }
+
+    #[test]
+    fn playlists_without_audio_have_no_tabs() {
+        let board = import_m3u(Path::new("/lists/empty.m3u"), "#EXTM3U\nreadme.txt\n");
+        assert!(board.tabs.is_empty());
+    }
+
+    #[test]
+    fn soundux_sounds_keep_their_settings() {
+        let config = json!({
+            "data": {
+                "tabs": [{
+                    "name": "Memes",
+                    "path": "/does/not/exist",
+                    "sounds": [
+                        {
+                            "name": "Bruh",
+                            "path": "/sounds/bruh.mp3",
+                            "hotkeys": [0xA2, 0xA0, 0x4B],
+                            "localVolume": 150
+                        },
+                        { "name": "horn", "path": "/sounds/horn.wav", "hotkey": "Alt+H" },
+                        { "name": "quiet", "path": "/sounds/quiet.ogg", "volume": 25 }
+                    ]
+                }]
+            }
+        });
+        let board = import_soundux(Path::new("/soundux.json"), &config.to_string()).unwrap();
+
+        assert_eq!(board.tabs.len(), 1);
+        assert_eq!(board.tabs[0].name, "Memes");
+        assert_eq!(board.tabs[0].manual_order().len(), 3);
+
+        let bruh = &board.clip_settings[Path::new("/sounds/bruh.mp3")];
+        assert_eq!(bruh.label.as_deref(), Some("Bruh"));
+        assert_eq!(bruh.hotkey.as_deref(), Some("Ctrl+Shift+K"));
+        assert_eq!(bruh.volume, Some(1.0));
+
+        let horn = &board.clip_settings[Path::new("/sounds/horn.wav")];
+        assert_eq!(horn.label, None);
+        assert_eq!(horn.hotkey.as_deref(), Some("Alt+H"));
+
+        let quiet = &board.clip_settings[Path::new("/sounds/quiet.ogg")];
+        assert_eq!(quiet.volume, Some(0.25));
+    }
+
+    #[test]
+    fn other_json_is_a_format_error() {
+        let error = import_soundux(Path::new("/other.json"), "[1, 2]").unwrap_err();
+        assert!(matches!(error, ImportError::Format { .. }));
+    }
+
+    #[test]
+    fn key_codes_need_exactly_one_key() {
+        let codes = |codes: &[u64]| -> Vec<Value> { codes.iter().map(|&c| json!(c)).collect() };
+
+        assert_eq!(hotkey_from_codes(&codes(&[0x70])).as_deref(), Some("F1"));
+        assert_eq!(
+            hotkey_from_codes(&codes(&[0x61, 0x12])).as_deref(),
+            Some("Alt+1")
+        );
+        assert_eq!(hotkey_from_codes(&codes(&[0x11])), None);
+        assert_eq!(hotkey_from_codes(&codes(&[0x41, 0x42])), None);
+        assert_eq!(hotkey_from_codes(&codes(&[0xFF, 0x41])), None);
+    }
+
+    #[test]
+    fn labels_only_differ_from_the_file_name() {
+        let path = Path::new("/sounds/horn.wav");
+        assert_eq!(clip_label(path, "horn"), None);
+        assert_eq!(clip_label(path, "horn.wav"), None);
+        assert_eq!(clip_label(path, "  "), None);
+        assert_eq!(clip_label(path, " Air horn ").as_deref(), Some("Air horn"));
+    }
+}
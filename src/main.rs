@@ -1,19 +1,41 @@
 mod app;
 mod audio;
+mod duplicates;
+mod files;
+mod history;
+mod importers;
+mod pack;
+mod query;
 mod saving;
+mod search;
+mod settings;
+mod shuffle;
+mod sorting;
 mod style;
 mod ui;
 
-use crate::app::SoundboardApp;
+use crate::app::{Flags, SoundboardApp, WINDOW_SIZE};
 
 use iced::{window, Application, Font, Settings, Size};
+use std::ffi::OsString;
 use style::{FONT_NAME, FONT_SIZE_DEFAULT};
 
 fn main() -> iced::Result {
+    if let Some(path) = arg("config") {
+        saving::set_config_path(path.into());
+    }
+    let profile = arg("profile").map(|name| name.to_string_lossy().into_owned());
+    if profile.is_some() && !saving::profiles_enabled() {
+        println!("Ignoring --profile, there are no profiles with an explicit config");
+    }
+
     SoundboardApp::run(Settings {
+        flags: Flags { profile },
         window: window::Settings {
-            size: Size::new(500.0, 800.0),
+            size: WINDOW_SIZE,
             min_size: Some(Size::new(400.0, 200.0)),
+            // closes once everything is saved
+            exit_on_close_request: false,
             ..Default::default()
         },
         default_font: Font::with_name(FONT_NAME),
@@ -21,3 +43,28 @@ fn main() -> iced::Result {
         ..Default::default()
     })
 }
+
+/// Reads the value of an option given as `--<name> <value>` or `--<name>=<value>`,
+/// like `--profile <name>` or `--config <path>`. Values don't have to be
+/// valid UTF-8, as paths may not be.
+fn arg(name: &str) -> Option<OsString> {
+    let flag = format!("--{name}");
+    let mut args = std::env::args_os().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == flag.as_str() {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .as_encoded_bytes()
+            .strip_prefix(flag.as_bytes())
+            .and_then(|rest| rest.strip_prefix(b"="))
+        {
+            // SAFETY: the bytes come from an `OsString` and are split right
+            // after an ASCII character
+            return Some(unsafe { OsString::from_encoded_bytes_unchecked(value.to_vec()) });
+        }
+    }
+
+    None
+}
@@ -0,0 +1,521 @@
+//! Board packs: tabs bundled with their audio files into one archive that
+//! can be unpacked on another machine.
+//!
+//! A pack is a plain tar archive. `board.json` at its root lists the tabs and
+//! clip settings with paths relative to the archive, and every tab that has
+//! files gets a folder of its own next to it.
+
+use crate::{
+    app::{Tab, TabKind},
+    audio::ClipSettings,
+    saving::FileError,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+/// File extension of board packs.
+pub const EXTENSION: &str = "sbpack";
+
+/// Version of the manifest layout written by this build.
+const VERSION: u32 = 1;
+
+const MANIFEST: &str = "board.json";
+
+/// What a pack holds besides the audio files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    tabs: Vec<Tab>,
+    #[serde(default)]
+    clip_settings: BTreeMap<PathBuf, ClipSettings>,
+}
+
+/// Tabs to add to the profile along with the settings of their clips.
+#[derive(Debug, Clone)]
+pub struct Board {
+    /// Where the tabs came from: the folder a pack was unpacked to, or the
+    /// file or folder imported from another app.
+    pub source: PathBuf,
+    pub tabs: Vec<Tab>,
+    pub clip_settings: BTreeMap<PathBuf, ClipSettings>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PackError {
+    File(FileError),
+    /// The archive is not a board pack, is damaged or comes from a newer
+    /// version of the app.
+    Format {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl PackError {
+    fn format(path: &Path, message: impl ToString) -> Self {
+        Self::Format {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for PackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::File(error) => write!(f, "{}", error),
+            PackError::Format { path, message } => write!(f, "{}: {}", path.display(), message),
+        }
+    }
+}
+
+/// Writes the given tabs, the files they show and the settings of those
+/// files into a pack at `path`, in the background.
+pub async fn export(
+    tabs: Vec<Tab>,
+    clip_settings: BTreeMap<PathBuf, ClipSettings>,
+    path: PathBuf,
+) -> Result<(), PackError> {
+    async_std::task::spawn_blocking(move || write_pack(tabs, &clip_settings, &path)).await
+}
+
+/// Unpacks a pack into a new folder inside `directory` in the background,
+/// and returns its tabs with their paths pointing there.
+pub async fn import(path: PathBuf, directory: PathBuf) -> Result<Board, PackError> {
+    async_std::task::spawn_blocking(move || unpack(&path, &directory)).await
+}
+
+fn write_pack(
+    tabs: Vec<Tab>,
+    clip_settings: &BTreeMap<PathBuf, ClipSettings>,
+    path: &Path,
+) -> Result<(), PackError> {
+    let mut folders = BTreeSet::new();
+    let mut files: Vec<(String, PathBuf)> = vec![];
+    let mut manifest = Manifest {
+        version: VERSION,
+        tabs: vec![],
+        clip_settings: BTreeMap::new(),
+    };
+
+    for mut tab in tabs {
+        if tab.is_smart() {
+            manifest.tabs.push(tab);
+            continue;
+        }
+
+        let folder = unique_name(&folder_name(&tab.name), |name| {
+            folders.contains(&name.to_lowercase())
+        });
+        folders.insert(folder.to_lowercase());
+
+        tab.load_clips();
+        let mut names = BTreeSet::new();
+        let mut packed: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+        for clip in &tab.clips {
+            let Some(name) = clip.path.file_name().map(|name| name.to_string_lossy()) else {
+                continue;
+            };
+            let name = unique_name(&name, |name| names.contains(&name.to_lowercase()));
+            names.insert(name.to_lowercase());
+
+            let entry = format!("{folder}/{name}");
+            packed.insert(clip.path.clone(), PathBuf::from(&entry));
+            files.push((entry, clip.path.clone()));
+        }
+
+        for (from, to) in &packed {
+            if let Some(settings) = clip_settings.get(from).filter(|s| !s.is_empty()) {
+                manifest.clip_settings.insert(to.clone(), settings.clone());
+            }
+        }
+        remap_tab(&mut tab, |path| packed.get(path).cloned());
+        if let TabKind::Directory { directory } = &mut tab.kind {
+            *directory = PathBuf::from(&folder);
+        }
+        manifest.tabs.push(tab);
+    }
+
+    let json =
+        serde_json::to_string_pretty(&manifest).map_err(|error| PackError::format(path, error))?;
+
+    // written next to the pack and moved into place once complete, so a
+    // failed export leaves nothing half-written behind
+    let temp_path = path.with_extension(format!("{EXTENSION}.tmp"));
+    let result = write_archive(&temp_path, &json, &files).and_then(|()| {
+        std::fs::rename(&temp_path, path)
+            .map_err(|error| PackError::File(FileError::new(path, &error)))
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+/// Writes the manifest and then every `(entry, source)` file into a tar
+/// archive at `path`.
+fn write_archive(path: &Path, json: &str, files: &[(String, PathBuf)]) -> Result<(), PackError> {
+    let write_error = |error: io::Error| PackError::File(FileError::new(path, &error));
+    let file = std::fs::File::create(path).map_err(write_error)?;
+    let mut archive = tar::Builder::new(io::BufWriter::new(file));
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(crate::history::now());
+    archive
+        .append_data(&mut header, MANIFEST, json.as_bytes())
+        .map_err(write_error)?;
+
+    for (entry, source) in files {
+        let mut file = std::fs::File::open(source)
+            .map_err(|error| PackError::File(FileError::new(source, &error)))?;
+        archive.append_file(entry, &mut file).map_err(write_error)?;
+    }
+
+    let file = archive
+        .into_inner()
+        .and_then(|writer| writer.into_inner().map_err(io::IntoInnerError::into_error))
+        .map_err(write_error)?;
+    file.sync_all().map_err(write_error)
+}
+
+/// Unpacks a pack into a new folder inside `directory`, named after the
+/// pack, and returns its tabs with their paths pointing there.
+fn unpack(path: &Path, directory: &Path) -> Result<Board, PackError> {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Board".to_owned());
+    let target = directory.join(unique_name(&folder_name(&stem), |name| {
+        directory.join(name).exists()
+    }));
+
+    // errors that don't come from the system are tar finding the archive damaged
+    let read_error = |error: io::Error| match error.raw_os_error() {
+        Some(_) => PackError::File(FileError::new(path, &error)),
+        None => PackError::format(path, format!("the archive is damaged, {error}")),
+    };
+    let file =
+        std::fs::File::open(path).map_err(|error| PackError::File(FileError::new(path, &error)))?;
+    let mut archive = tar::Archive::new(io::BufReader::new(file));
+    let mut manifest = None;
+
+    for entry in archive.entries().map_err(read_error)? {
+        let mut entry = entry.map_err(read_error)?;
+        // folders and links are left out, the files carry their own paths
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path().map_err(read_error)?.into_owned();
+
+        if name == Path::new(MANIFEST) {
+            let mut json = String::new();
+            entry.read_to_string(&mut json).map_err(read_error)?;
+            manifest = Some(
+                serde_json::from_str::<Manifest>(&json)
+                    .map_err(|error| PackError::format(path, error))?,
+            );
+        } else if let Some(relative) = relative_path(&name) {
+            let file_path = target.join(relative);
+            let write_error =
+                |error: io::Error| PackError::File(FileError::new(&file_path, &error));
+
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent).map_err(write_error)?;
+            }
+            let mut file = std::fs::File::create_new(&file_path).map_err(write_error)?;
+            io::copy(&mut entry, &mut file).map_err(write_error)?;
+        } else {
+            println!("Skipping {} in {}", name.display(), path.display());
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| {
+        PackError::format(
+            path,
+            format!("{} is missing, this is not a board pack", MANIFEST),
+        )
+    })?;
+    if manifest.version > VERSION {
+        return Err(PackError::format(
+            path,
+            format!(
+                "the pack is version {} but only up to {} is supported",
+                manifest.version, VERSION
+            ),
+        ));
+    }
+
+    let unpacked = |relative: &Path| relative_path(relative).map(|path| target.join(path));
+    let mut tabs = manifest.tabs;
+    for tab in tabs.iter_mut() {
+        remap_tab(tab, unpacked);
+        if let TabKind::Directory { directory } = &mut tab.kind {
+            *directory = unpacked(directory).unwrap_or_else(|| target.clone());
+        }
+    }
+    let clip_settings = manifest
+        .clip_settings
+        .into_iter()
+        .filter_map(|(path, settings)| Some((unpacked(&path)?, settings)))
+        .collect();
+
+    Ok(Board {
+        source: target,
+        tabs,
+        clip_settings,
+    })
+}
+
+/// Rewrites the file paths a tab keeps, dropping the ones `map` has no
+/// replacement for.
+fn remap_tab(tab: &mut Tab, map: impl Fn(&Path) -> Option<PathBuf>) {
+    let remap = |paths: &mut Vec<PathBuf>| {
+        *paths = paths.iter().filter_map(|path| map(path)).collect();
+    };
+
+    remap(&mut tab.manual_order);
+    for group in tab.variant_groups.iter_mut() {
+        remap(&mut group.clips);
+    }
+    tab.variant_groups.retain(|group| group.clips.len() > 1);
+    if let TabKind::Collection { collection } = &mut tab.kind {
+        remap(collection);
+    }
+}
+
+/// Returns the path if it stays inside the folder it is relative to.
+fn relative_path(path: &Path) -> Option<&Path> {
+    let inside = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+
+    (inside && path.components().next().is_some()).then_some(path)
+}
+
+/// Turns a tab name into a folder name that works on any system.
+fn folder_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || "/\\:*?\"<>|".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let name = name.trim().trim_matches('.');
+
+    if name.is_empty() {
+        "Tab".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Appends " (2)", " (3)" and so on to a name until `taken` accepts it,
+/// keeping any extension at the end.
+fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(name) {
+        return name.to_owned();
+    }
+
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| format!("{stem} ({n}){extension}"))
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A fresh, empty folder for one test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("soundboard-pack-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes a short, silent 16-bit mono WAV file.
+    pub(crate) fn write_wav(path: &Path, samples: u32) {
+        let data = samples * 2;
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data.to_le_bytes());
+        bytes.resize(bytes.len() + data as usize, 0);
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    /// Packs a collection of one short clip and returns the pack's path.
+    fn write_test_pack(dir: &Path, tab_name: &str) -> PathBuf {
+        let clip = dir.join("clip.wav");
+        write_wav(&clip, 800);
+        let mut collection = Tab::new_collection(tab_name.to_owned());
+        collection.add_to_collection(&[clip]);
+
+        let pack = dir.join("test.sbpack");
+        write_pack(vec![collection], &BTreeMap::new(), &pack).unwrap();
+        pack
+    }
+
+    #[test]
+    fn long_names_round_trip() {
+        let dir = temp_dir("long-names");
+        let name = "A tab with a name far too long for the name field of a tar header".repeat(3);
+        let pack = write_test_pack(&dir, &name);
+
+        let board = unpack(&pack, &dir).unwrap();
+        let clip = dir.join("test").join(&name).join("clip.wav");
+        assert_eq!(
+            board.tabs[0].kind,
+            TabKind::Collection {
+                collection: vec![clip.clone()]
+            }
+        );
+        assert!(clip.is_file());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn damaged_pack_is_refused() {
+        let dir = temp_dir("damaged");
+        let pack = write_test_pack(&dir, "Tab");
+        let mut bytes = std::fs::read(&pack).unwrap();
+
+        // a header that no longer matches its checksum
+        bytes[0] ^= 1;
+        std::fs::write(&pack, &bytes).unwrap();
+        assert!(matches!(unpack(&pack, &dir), Err(PackError::Format { .. })));
+
+        // no manifest at all
+        std::fs::write(&pack, [0; 1024]).unwrap();
+        assert!(matches!(unpack(&pack, &dir), Err(PackError::Format { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_export_leaves_the_old_pack() {
+        let dir = temp_dir("failed");
+        let pack = dir.join("board.sbpack");
+        std::fs::write(&pack, "old pack").unwrap();
+        // nothing can be written where the new pack is put together
+        std::fs::create_dir(dir.join("board.sbpack.tmp")).unwrap();
+
+        let tabs = vec![Tab::new_smart("Short".to_owned(), "duration<5s".to_owned())];
+        assert!(write_pack(tabs.clone(), &BTreeMap::new(), &pack).is_err());
+        assert_eq!(std::fs::read_to_string(&pack).unwrap(), "old pack");
+
+        std::fs::remove_dir(dir.join("board.sbpack.tmp")).unwrap();
+        write_pack(tabs, &BTreeMap::new(), &pack).unwrap();
+        assert!(!dir.join("board.sbpack.tmp").exists());
+        assert_eq!(unpack(&pack, &dir).unwrap().tabs.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn only_paths_inside_the_pack() {
+        assert!(relative_path(Path::new("Memes/airhorn.wav")).is_some());
+        assert!(relative_path(Path::new("../airhorn.wav")).is_none());
+        assert!(relative_path(Path::new("/airhorn.wav")).is_none());
+        assert!(relative_path(Path::new("")).is_none());
+    }
+
+    #[test]
+    fn unique_names_keep_their_extension() {
+        let taken = ["clip.wav", "clip (2).wav"];
+        assert_eq!(
+            unique_name("clip.wav", |name| taken.contains(&name)),
+            "clip (3).wav"
+        );
+        assert_eq!(
+            unique_name("other.wav", |name| taken.contains(&name)),
+            "other.wav"
+        );
+        assert_eq!(folder_name("a/b: c?"), "a_b_ c_");
+        assert_eq!(folder_name(" .. "), "Tab");
+    }
+
+    #[test]
+    fn export_and_import() {
+        let dir = temp_dir("round-trip");
+        let sounds = dir.join("sounds");
+        std::fs::create_dir_all(&sounds).unwrap();
+        let airhorn = sounds.join("airhorn.wav");
+        let bruh = sounds.join("bruh.wav");
+        write_wav(&airhorn, 800);
+        write_wav(&bruh, 1600);
+
+        let mut collection = Tab::new_collection("Favorites: best".to_owned());
+        collection.add_to_collection(std::slice::from_ref(&bruh));
+        let tabs = vec![Tab::new_directory(sounds.clone()), collection];
+        let clip_settings = BTreeMap::from([(
+            airhorn.clone(),
+            ClipSettings {
+                label: Some("Horn".to_owned()),
+                ..Default::default()
+            },
+        )]);
+
+        let pack = dir.join("memes.sbpack");
+        write_pack(tabs, &clip_settings, &pack).unwrap();
+        let board = unpack(&pack, &dir).unwrap();
+
+        let target = dir.join("memes");
+        assert_eq!(board.source, target);
+        assert_eq!(board.tabs.len(), 2);
+        assert_eq!(
+            board.tabs[0].kind,
+            TabKind::Directory {
+                directory: target.join("sounds")
+            }
+        );
+        assert_eq!(
+            board.tabs[1].kind,
+            TabKind::Collection {
+                collection: vec![target.join("Favorites_ best/bruh.wav")]
+            }
+        );
+        assert_eq!(
+            std::fs::read(target.join("sounds/airhorn.wav")).unwrap(),
+            std::fs::read(&airhorn).unwrap()
+        );
+        assert_eq!(
+            board.clip_settings[&target.join("sounds/airhorn.wav")].label,
+            Some("Horn".to_owned())
+        );
+
+        // a second import goes next to the first instead of over it
+        let again = unpack(&pack, &dir).unwrap();
+        assert_eq!(again.source, dir.join("memes (2)"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
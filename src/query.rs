@@ -0,0 +1,380 @@
+use crate::{
+    audio::{AudioClip, ClipSettings},
+    history::PlayStats,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    time::Duration,
+};
+
+const KEYWORDS: [&str; 9] = [
+    "duration", "played", "tag", "folder", "name", "title", "artist", "album", "comment",
+];
+
+lazy_static! {
+    static ref TERM: Regex = Regex::new(
+        r#"(?ix)
+        (?P<negate>-|\bnot\s+)?
+        (?:
+            \bduration\s*(?P<op><=|>=|<|>|=)\s*(?P<amount>\d+(?:\.\d+)?)\s*(?P<unit>ms|s|m|h)?\b
+          | \bplayed\s+in\s+(?:the\s+)?last\s+(?P<count>\d+)\s*(?P<period>hours?|days?|weeks?)\b
+          | \bnever\s+played\b(?P<never>)
+          | \b(?:is:)?(?P<favorite>favou?rites?)\b
+          | \b(?P<field>tag|folder|name|title|artist|album|comment)
+                (?::|\s+contains\s+)\s*(?P<value>"[^"]*"|\S+)
+          | (?P<word>"[^"]*"|\S+)
+        )"#
+    )
+    .unwrap();
+}
+
+/// A saved search that decides which clips a smart tab shows.
+///
+/// Terms are separated by whitespace and all of them have to match, e.g.
+/// `duration < 5s tag:meme played in last 7 days folder contains airhorns`.
+/// Any term can be negated with a leading `-` or `not`.
+#[derive(Debug, Clone)]
+pub struct Query {
+    terms: Vec<(bool, Condition)>,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    /// Compares durations rounded to `precision` seconds, the precision the
+    /// amount was written with, so "2" means anything that rounds to 2s.
+    Duration {
+        ordering: Ordering,
+        amount: Duration,
+        precision: f64,
+    },
+    PlayedWithin(Duration),
+    NeverPlayed,
+    Favorite,
+    Field(Field, String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Ordering {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Tag,
+    Folder,
+    Name,
+    Title,
+    Artist,
+    Album,
+    Comment,
+}
+
+/// Everything besides the clip itself a query may look at.
+pub struct QueryContext<'a> {
+    pub favorites: &'a BTreeSet<PathBuf>,
+    pub play_stats: &'a PlayStats,
+    pub clip_settings: &'a BTreeMap<PathBuf, ClipSettings>,
+    /// Name of the tab the clip was found in, matched by `folder`.
+    pub tab_name: &'a str,
+    pub now: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError(pub String);
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut terms = vec![];
+
+        for captures in TERM.captures_iter(text) {
+            let negate = captures.name("negate").is_some();
+
+            let condition = if let Some(op) = captures.name("op") {
+                let amount: f64 = captures["amount"].parse().unwrap_or_default();
+                let unit = match captures.name("unit").map(|unit| unit.as_str()) {
+                    Some("ms") => 0.001,
+                    Some("m") => 60.0,
+                    Some("h") => 3600.0,
+                    _ => 1.0,
+                };
+                let decimals = captures["amount"]
+                    .split_once('.')
+                    .map_or(0, |(_, decimals)| decimals.len().min(9));
+                let precision = unit / 10f64.powi(decimals as i32);
+                let ordering = match op.as_str() {
+                    "<" => Ordering::Less,
+                    "<=" => Ordering::LessEqual,
+                    ">=" => Ordering::GreaterEqual,
+                    ">" => Ordering::Greater,
+                    _ => Ordering::Equal,
+                };
+
+                let amount = Duration::try_from_secs_f64(amount * unit).map_err(|_| {
+                    ParseError(format!("Duration too long near \"{}\"", captures[0].trim()))
+                })?;
+
+                Condition::Duration {
+                    ordering,
+                    amount,
+                    precision,
+                }
+            } else if let Some(count) = captures.name("count") {
+                let too_long =
+                    || ParseError(format!("Period too long near \"{}\"", captures[0].trim()));
+                let count: u64 = count.as_str().parse().map_err(|_| too_long())?;
+                let period = captures["period"].to_lowercase();
+                let seconds = if period.starts_with("hour") {
+                    3600
+                } else if period.starts_with("week") {
+                    7 * 86400
+                } else {
+                    86400
+                };
+
+                let seconds = count.checked_mul(seconds).ok_or_else(too_long)?;
+
+                Condition::PlayedWithin(Duration::from_secs(seconds))
+            } else if captures.name("never").is_some() {
+                Condition::NeverPlayed
+            } else if captures.name("favorite").is_some() {
+                Condition::Favorite
+            } else if let Some(field) = captures.name("field") {
+                let field = match field.as_str().to_lowercase().as_str() {
+                    "tag" => Field::Tag,
+                    "folder" => Field::Folder,
+                    "title" => Field::Title,
+                    "artist" => Field::Artist,
+                    "album" => Field::Album,
+                    "comment" => Field::Comment,
+                    _ => Field::Name,
+                };
+
+                Condition::Field(field, unquote(&captures["value"]))
+            } else {
+                let word = unquote(&captures["word"]);
+                if is_keyword(&word) {
+                    return Err(ParseError(format!("Incomplete condition near \"{word}\"")));
+                }
+
+                Condition::Field(Field::Name, word)
+            };
+
+            terms.push((negate, condition));
+        }
+
+        Ok(Self { terms })
+    }
+
+    pub fn matches(&self, clip: &AudioClip, context: &QueryContext) -> bool {
+        self.terms
+            .iter()
+            .all(|(negate, condition)| condition.matches(clip, context) != *negate)
+    }
+}
+
+impl Condition {
+    fn matches(&self, clip: &AudioClip, context: &QueryContext) -> bool {
+        match self {
+            Condition::Duration {
+                ordering,
+                amount,
+                precision,
+            } => {
+                let rounded = |duration: &Duration| (duration.as_secs_f64() / precision).round();
+                let (duration, amount) = (rounded(&clip.duration), rounded(amount));
+
+                match ordering {
+                    Ordering::Less => duration < amount,
+                    Ordering::LessEqual => duration <= amount,
+                    Ordering::Equal => duration == amount,
+                    Ordering::GreaterEqual => duration >= amount,
+                    Ordering::Greater => duration > amount,
+                }
+            }
+            Condition::PlayedWithin(period) => context
+                .play_stats
+                .get(&clip.path)
+                .and_then(|stats| stats.last_played)
+                .is_some_and(|last_played| {
+                    context.now.saturating_sub(last_played) <= period.as_secs()
+                }),
+            Condition::NeverPlayed => {
+                context
+                    .play_stats
+                    .get(&clip.path)
+                    .map_or(0, |stats| stats.play_count)
+                    == 0
+            }
+            Condition::Favorite => context.favorites.contains(&clip.path),
+            Condition::Field(field, value) => {
+                let folder = clip
+                    .path
+                    .parent()
+                    .and_then(|parent| parent.file_name())
+                    .map(|name| name.to_string_lossy().into_owned());
+
+                let candidates = match field {
+                    Field::Name => vec![Some(clip.name.clone())],
+                    Field::Title => vec![clip.title.clone()],
+                    Field::Artist => vec![clip.artist.clone()],
+                    Field::Album => vec![clip.album.clone()],
+                    Field::Comment => vec![clip.comment.clone()],
+                    Field::Folder => vec![folder, Some(context.tab_name.to_owned())],
+                    Field::Tag => {
+                        let user_tags = context
+                            .clip_settings
+                            .get(&clip.path)
+                            .into_iter()
+                            .flat_map(|settings| settings.tags.iter().cloned().map(Some));
+
+                        vec![
+                            clip.title.clone(),
+                            clip.artist.clone(),
+                            clip.album.clone(),
+                            clip.comment.clone(),
+                        ]
+                        .into_iter()
+                        .chain(user_tags)
+                        .collect()
+                    }
+                };
+
+                candidates
+                    .into_iter()
+                    .flatten()
+                    .any(|candidate| contains_ignore_case(&candidate, value))
+            }
+        }
+    }
+}
+
+/// Whether a leftover word starts like a condition that failed to parse.
+fn is_keyword(word: &str) -> bool {
+    let word = word.to_lowercase();
+
+    KEYWORDS.iter().any(|keyword| {
+        word.strip_prefix(keyword)
+            .is_some_and(|rest| !rest.starts_with(char::is_alphanumeric))
+    })
+}
+
+fn unquote(value: &str) -> String {
+    value.trim_matches('"').to_owned()
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(name: &str, seconds: u64) -> AudioClip {
+        AudioClip {
+            name: name.to_owned(),
+            path: PathBuf::from("sounds/memes").join(name),
+            duration: Duration::from_secs(seconds),
+            title: None,
+            artist: None,
+            album: None,
+            comment: None,
+            cover: None,
+            modified: None,
+            added: None,
+        }
+    }
+
+    fn matches(query: &str, clip: &AudioClip, favorites: &BTreeSet<PathBuf>) -> bool {
+        let context = QueryContext {
+            favorites,
+            play_stats: &PlayStats::default(),
+            clip_settings: &BTreeMap::new(),
+            tab_name: "Memes",
+            now: 0,
+        };
+
+        Query::parse(query).unwrap().matches(clip, &context)
+    }
+
+    #[test]
+    fn duration_units() {
+        let none = BTreeSet::new();
+        let airhorn = clip("airhorn.mp3", 3);
+
+        assert!(matches("duration < 5s", &airhorn, &none));
+        assert!(matches("duration>2500ms", &airhorn, &none));
+        assert!(!matches("duration >= 1m", &airhorn, &none));
+        assert!(matches("duration < 0.1h", &airhorn, &none));
+    }
+
+    #[test]
+    fn durations_round_to_the_query() {
+        let none = BTreeSet::new();
+        let mut long = clip("long.mp3", 0);
+        long.duration = Duration::from_millis(2900);
+        let mut short = clip("short.mp3", 0);
+        short.duration = Duration::from_millis(2400);
+
+        for (query, long_matches, short_matches) in [
+            ("duration=2", false, true),
+            ("duration<=2", false, true),
+            ("duration>=3", true, false),
+            ("duration<3", false, true),
+            ("duration=3", true, false),
+            ("duration=2.9", true, false),
+            ("duration>2.4s", true, false),
+            ("duration=2400ms", false, true),
+        ] {
+            assert_eq!(matches(query, &long, &none), long_matches, "{query}");
+            assert_eq!(matches(query, &short, &none), short_matches, "{query}");
+        }
+    }
+
+    #[test]
+    fn negated_and_combined_terms() {
+        let airhorn = clip("airhorn.mp3", 3);
+        let favorites = BTreeSet::from([airhorn.path.clone()]);
+
+        assert!(matches("favorite folder:memes", &airhorn, &favorites));
+        assert!(!matches("-favorite", &airhorn, &favorites));
+        assert!(matches("not name:bruh air", &airhorn, &favorites));
+        assert!(matches(
+            "name:\"airhorn.mp3\" never played",
+            &airhorn,
+            &favorites
+        ));
+    }
+
+    #[test]
+    fn incomplete_condition() {
+        assert!(Query::parse("duration").is_err());
+        assert!(Query::parse("tag:").is_err());
+    }
+
+    #[test]
+    fn duration_out_of_range() {
+        assert!(Query::parse("duration>99999999999999999999h").is_err());
+    }
+
+    #[test]
+    fn period_out_of_range() {
+        assert!(Query::parse("played in last 99999999999999999999 days").is_err());
+        assert!(Query::parse("played in last 9999999999999999 weeks").is_err());
+        assert!(Query::parse("played in last 2 weeks").is_ok());
+    }
+}
@@ -1,82 +1,775 @@
-use crate::app::Tab;
+use crate::{
+    app::Tab,
+    audio::ClipSettings,
+    files::ImportMode,
+    history::{PlayRecord, PlayStats},
+};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::OnceLock,
+};
+
+/// Version of the config layout written by this build. Bump it along with
+/// adding a step to [`MIGRATIONS`] whenever a change would keep older
+/// configs from loading.
+pub const VERSION: u32 = 1;
+
+/// The profile whose config lives at the old, fixed `config.json`.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Environment variable pointing at the config to use instead of the one in
+/// the user's data directory.
+pub const CONFIG_VAR: &str = "SOUNDBOARD_CONFIG";
+
+/// The config given on the command line, if any.
+static CONFIG_ARG: OnceLock<PathBuf> = OnceLock::new();
+
+/// How many backups of each profile's config are kept.
+const BACKUP_LIMIT: usize = 10;
+/// Minimum time between two backups of the config, in seconds.
+const BACKUP_INTERVAL: u64 = 60 * 60;
+
+/// Steps that bring a config up to date, the one at index `n` turning a
+/// version `n` config into version `n + 1`.
+const MIGRATIONS: [fn(&mut Value); VERSION as usize] = [migrate_unversioned];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SavedState {
+    /// Configs written before versioning count as version 0.
+    #[serde(default)]
+    pub version: u32,
     pub tabs: Vec<Tab>,
     pub current_tab: usize,
     pub global_volume: f32,
     pub global_speed: f64,
+    #[serde(default)]
+    pub favorites: BTreeSet<PathBuf>,
+    #[serde(default)]
+    pub play_stats: PlayStats,
+    #[serde(default)]
+    pub history: Vec<PlayRecord>,
+    #[serde(default)]
+    pub clip_settings: BTreeMap<PathBuf, ClipSettings>,
+    /// Contents of the files in `clip_settings`, to find them again after
+    /// they were renamed or moved outside the app.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub clip_hashes: BTreeMap<PathBuf, u64>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub hidden_clips: BTreeSet<PathBuf>,
+    #[serde(default)]
+    pub import_mode: ImportMode,
+    #[serde(default)]
+    pub session: Session,
+}
+
+/// How the app was left, put back the way it was on the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default = "enabled")]
+    pub volume_enabled: bool,
+    #[serde(default = "enabled")]
+    pub speed_enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowState>,
+    #[serde(default)]
+    pub favorites_selected: bool,
+    /// Relative scroll position of the favorites.
+    #[serde(default)]
+    pub favorites_scroll: f32,
+    #[serde(default)]
+    pub history_open: bool,
+    #[serde(default)]
+    pub duplicates_open: bool,
+    /// Clips that were playing when the app was closed, brought back paused.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub playing: Vec<PlayingClip>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            volume_enabled: true,
+            speed_enabled: true,
+            window: None,
+            favorites_selected: false,
+            favorites_scroll: 0.0,
+            history_open: false,
+            duplicates_open: false,
+            playing: vec![],
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowState {
+    pub width: f32,
+    pub height: f32,
+    /// Where the window was, once it has been moved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<(f32, f32)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayingClip {
+    pub path: PathBuf,
+    /// Where playback was, in seconds.
+    pub position: f64,
+    /// When the play was recorded in the history, so finishing it later
+    /// updates the same record.
+    pub started: u64,
+    /// How long the clip had been audible, in seconds.
+    pub played: f64,
+}
+
+/// An io error along with the file it happened on.
+#[derive(Debug, Clone)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub kind: std::io::ErrorKind,
+    pub message: String,
+}
+
+impl FileError {
+    pub fn new(path: &std::path::Path, error: &std::io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// Where and why the config could not be read as JSON.
+#[derive(Debug, Clone)]
+pub struct FormatError {
+    /// Line of the problem, starting at 1, or 0 when it isn't known.
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl FormatError {
+    fn new(error: &serde_json::Error) -> Self {
+        let message = error.to_string();
+        let position = format!(" at line {} column {}", error.line(), error.column());
+
+        Self {
+            line: error.line(),
+            column: error.column(),
+            message: message
+                .strip_suffix(&position)
+                .unwrap_or(&message)
+                .to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(
+                f,
+                "{} (line {}, column {})",
+                self.message, self.line, self.column
+            )
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum LoadError {
-    File,
-    Format,
+    /// There is no config yet.
+    NotFound,
+    File(FileError),
+    /// The config could not be parsed. It was copied to `backup`, unless
+    /// that failed too.
+    Format {
+        path: PathBuf,
+        error: FormatError,
+        backup: Option<PathBuf>,
+    },
+    /// The config was written by a newer version of the app.
+    Version {
+        path: PathBuf,
+        version: u64,
+        backup: Option<PathBuf>,
+    },
+}
+
+impl LoadError {
+    /// Whether the config on disk may be replaced, which is only the case
+    /// when there was none or a copy of it was made.
+    pub fn can_overwrite(&self) -> bool {
+        match self {
+            LoadError::NotFound => true,
+            LoadError::File(_) => false,
+            LoadError::Format { backup, .. } | LoadError::Version { backup, .. } => {
+                backup.is_some()
+            }
+        }
+    }
+
+    /// Where a copy of the config that failed to load was kept.
+    pub fn backup(&self) -> Option<&std::path::Path> {
+        match self {
+            LoadError::Format { backup, .. } | LoadError::Version { backup, .. } => {
+                backup.as_deref()
+            }
+            LoadError::NotFound | LoadError::File(_) => None,
+        }
+    }
+
+    fn with_backup(self, path: Option<PathBuf>) -> Self {
+        match self {
+            LoadError::Format {
+                path: file, error, ..
+            } => LoadError::Format {
+                path: file,
+                error,
+                backup: path,
+            },
+            LoadError::Version {
+                path: file,
+                version,
+                ..
+            } => LoadError::Version {
+                path: file,
+                version,
+                backup: path,
+            },
+            error => error,
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::NotFound => write!(f, "No config found"),
+            LoadError::File(error) => write!(f, "Could not read {error}"),
+            LoadError::Format { path, error, .. } => {
+                write!(f, "Could not understand {}: {}", path.display(), error)
+            }
+            LoadError::Version { path, version, .. } => write!(
+                f,
+                "{} is from a newer version of the app (config version {})",
+                path.display(),
+                version
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum SaveError {
-    File,
-    Write,
-    Format,
+    /// Creating the config or swapping it in failed.
+    File(FileError),
+    Write(FileError),
+    Format(String),
+}
+
+impl SaveError {
+    /// The kind of io error behind the failure, if there was one.
+    pub fn kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            SaveError::File(error) | SaveError::Write(error) => Some(error.kind),
+            SaveError::Format(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::File(error) => write!(f, "Could not save {error}"),
+            SaveError::Write(error) => write!(f, "Could not write {error}"),
+            SaveError::Format(message) => write!(f, "Could not encode the config: {message}"),
+        }
+    }
+}
+
+/// Uses the given config instead of looking for one. Only the first call
+/// has an effect.
+pub fn set_config_path(path: PathBuf) {
+    let path = std::path::absolute(&path).unwrap_or(path);
+    let _ = CONFIG_ARG.set(path);
+}
+
+/// Returns the config given with `--config`, or else the one in [`CONFIG_VAR`].
+fn explicit_config() -> Option<PathBuf> {
+    if let Some(path) = CONFIG_ARG.get() {
+        return Some(path.clone());
+    }
+
+    let path = std::env::var_os(CONFIG_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)?;
+    Some(std::path::absolute(&path).unwrap_or(path))
+}
+
+/// Returns the config to use instead of the one in the user's data
+/// directory: an [explicit one](explicit_config), then a `config.json` next
+/// to the executable, which makes the app portable.
+fn config_override() -> Option<PathBuf> {
+    static CONFIG: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    CONFIG
+        .get_or_init(|| {
+            explicit_config().or_else(|| {
+                let portable = std::env::current_exe().ok()?.parent()?.join("config.json");
+                portable.is_file().then_some(portable)
+            })
+        })
+        .clone()
+}
+
+/// Whether there can be profiles besides the default one. A config given
+/// with `--config` or [`CONFIG_VAR`] is used on its own, so there are no
+/// other profiles and the last one used isn't remembered. Portable configs
+/// keep them, next to the executable.
+pub fn profiles_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+
+    *ENABLED.get_or_init(|| explicit_config().is_none())
+}
+
+/// Returns the directory the config and other app data are stored in. With
+/// an overridden config, that's the folder the config is in, so its
+/// backups, trash and settings file are kept next to it.
+pub fn data_dir() -> std::path::PathBuf {
+    let config_dir = config_override().and_then(|config| Some(config.parent()?.to_path_buf()));
+
+    if let Some(config_dir) = config_dir {
+        config_dir
+    } else if let Some(project_dirs) =
+        directories_next::ProjectDirs::from("rs", "Iced", "Soundboard")
+    {
+        project_dirs.data_dir().into()
+    } else {
+        std::env::current_dir().unwrap_or_default()
+    }
+}
+
+/// Returns the directory backups of the config are kept in.
+pub fn backup_dir() -> std::path::PathBuf {
+    data_dir().join("backups")
+}
+
+fn profile_dir() -> std::path::PathBuf {
+    data_dir().join("profiles")
+}
+
+/// Returns the names of all profiles, the default one first.
+pub fn profiles() -> Vec<String> {
+    if !profiles_enabled() {
+        return vec![DEFAULT_PROFILE.to_owned()];
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(profile_dir())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| Some(decode_profile_name(path.file_stem()?.to_str()?)))
+        .filter(|name| name != DEFAULT_PROFILE)
+        .collect();
+    names.sort_by(|a, b| crate::sorting::natural_cmp(a, b));
+    names.insert(0, DEFAULT_PROFILE.to_owned());
+
+    names
+}
+
+/// Returns the profile used last, or the default one.
+pub fn last_profile() -> String {
+    if !profiles_enabled() {
+        return DEFAULT_PROFILE.to_owned();
+    }
+
+    std::fs::read_to_string(data_dir().join("last-profile"))
+        .ok()
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_owned())
+}
+
+/// Remembers the profile to open on the next start.
+pub fn remember_profile(name: &str) {
+    if !profiles_enabled() {
+        return;
+    }
+
+    let result = std::fs::create_dir_all(data_dir())
+        .and_then(|_| std::fs::write(data_dir().join("last-profile"), name));
+    if let Err(error) = result {
+        println!("Could not remember profile {}: {}", name, error);
+    }
+}
+
+/// Escapes the characters of a profile name that can't be part of a file name.
+fn encode_profile_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c == '%' || c.is_control() || "/\\:*?\"<>|".contains(c) {
+                format!("%{:02X}", c as u32)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+fn decode_profile_name(encoded: &str) -> String {
+    let mut name = String::new();
+    let mut rest = encoded;
+
+    while let Some(idx) = rest.find('%') {
+        name.push_str(&rest[..idx]);
+        let escaped = rest
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                name.push(byte as char);
+                rest = &rest[idx + 3..];
+            }
+            None => {
+                name.push('%');
+                rest = &rest[idx + 1..];
+            }
+        }
+    }
+    name.push_str(rest);
+
+    name
+}
+
+/// The start of the names of a profile's backups.
+fn backup_prefix(profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        "config".to_owned()
+    } else {
+        format!("profile-{}", encode_profile_name(profile))
+    }
+}
+
+/// Returns the rotating backups of a profile's config, newest first.
+fn backups(profile: &str) -> Vec<PathBuf> {
+    let prefix = backup_prefix(profile);
+
+    let mut backups: Vec<(u64, PathBuf)> = std::fs::read_dir(backup_dir())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let (name, time) = path.file_stem()?.to_str()?.rsplit_once('-')?;
+            (name == prefix).then_some((time.parse().ok()?, path))
+        })
+        .collect();
+    backups.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+/// When a rotating backup was made, read from its name.
+fn backup_time(path: &std::path::Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.rsplit_once('-')?.1.parse().ok()
+}
+
+/// Copies a profile's config into the backups unless the newest one is
+/// recent, dropping the oldest ones past [`BACKUP_LIMIT`].
+fn rotate_backups(config: &std::path::Path, profile: &str) -> std::io::Result<()> {
+    if !config.exists() {
+        return Ok(());
+    }
+
+    let now = crate::history::now();
+    let backups = backups(profile);
+    let is_recent = backups
+        .first()
+        .and_then(|path| backup_time(path))
+        .is_some_and(|time| now.saturating_sub(time) < BACKUP_INTERVAL);
+    if is_recent {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(backup_dir())?;
+    std::fs::copy(
+        config,
+        backup_dir().join(format!("{}-{}.json", backup_prefix(profile), now)),
+    )?;
+
+    for old in backups.iter().skip(BACKUP_LIMIT - 1) {
+        std::fs::remove_file(old)?;
+    }
+
+    Ok(())
 }
 
 impl SavedState {
-    fn path() -> std::path::PathBuf {
-        let mut path = if let Some(project_dirs) =
-            directories_next::ProjectDirs::from("rs", "Iced", "Soundboard")
-        {
-            project_dirs.data_dir().into()
+    /// Returns where a profile's hand-editable settings are kept, next to its config.
+    pub fn settings_path(profile: &str) -> std::path::PathBuf {
+        Self::path(profile).with_extension("toml")
+    }
+
+    fn path(profile: &str) -> std::path::PathBuf {
+        if profile == DEFAULT_PROFILE {
+            config_override().unwrap_or_else(|| data_dir().join("config.json"))
         } else {
-            std::env::current_dir().unwrap_or_default()
-        };
+            profile_dir().join(format!("{}.json", encode_profile_name(profile)))
+        }
+    }
+
+    pub async fn load(profile: String) -> Result<SavedState, LoadError> {
+        let path = Self::path(&profile);
 
-        path.push("config.json");
+        let contents = match async_std::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Err(LoadError::NotFound)
+            }
+            Err(error) => return Err(LoadError::File(FileError::new(&path, &error))),
+        };
 
-        path
+        Self::parse(&path, &contents).map_err(|error| {
+            let backup = backup_unreadable(&path, &profile);
+            error.with_backup(backup)
+        })
     }
 
-    pub async fn load() -> Result<SavedState, LoadError> {
-        use async_std::prelude::*;
+    /// Reads a backup picked by the user, leaving it as it is when it can't be loaded.
+    pub async fn load_backup(path: PathBuf) -> Result<SavedState, LoadError> {
+        let contents = async_std::fs::read_to_string(&path)
+            .await
+            .map_err(|error| LoadError::File(FileError::new(&path, &error)))?;
 
-        let mut contents = String::new();
+        Self::parse(&path, &contents)
+    }
 
-        let mut file = async_std::fs::File::open(Self::path())
-            .await
-            .map_err(|_| LoadError::File)?;
+    /// Reads a config of any known version, migrating it to the current layout.
+    fn parse(path: &std::path::Path, contents: &str) -> Result<SavedState, LoadError> {
+        let format_error = |error: serde_json::Error| LoadError::Format {
+            path: path.to_path_buf(),
+            error: FormatError::new(&error),
+            backup: None,
+        };
 
-        file.read_to_string(&mut contents)
-            .await
-            .map_err(|_| LoadError::File)?;
+        let mut value: Value = serde_json::from_str(contents).map_err(format_error)?;
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        let version = u32::try_from(version)
+            .ok()
+            .filter(|version| *version <= VERSION)
+            .ok_or_else(|| LoadError::Version {
+                path: path.to_path_buf(),
+                version,
+                backup: None,
+            })?;
+
+        for migrate in &MIGRATIONS[version as usize..] {
+            migrate(&mut value);
+        }
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".to_owned(), VERSION.into());
+        }
 
-        serde_json::from_str(&contents).map_err(|_| LoadError::Format)
+        serde_json::from_value(value).map_err(|error| {
+            // positions are lost once parsed into a value, so read the text
+            // again to find where it went wrong
+            let positioned = serde_json::from_str::<SavedState>(contents).err();
+            format_error(positioned.unwrap_or(error))
+        })
     }
 
-    pub async fn save(self) -> Result<(), SaveError> {
+    pub async fn save(self, profile: String) -> Result<(), SaveError> {
         use async_std::prelude::*;
 
-        let json = serde_json::to_string_pretty(&self).map_err(|_| SaveError::Format)?;
+        let json = serde_json::to_string_pretty(&self)
+            .map_err(|error| SaveError::Format(error.to_string()))?;
 
-        let path = Self::path();
+        let path = Self::path(&profile);
 
         if let Some(dir) = path.parent() {
             async_std::fs::create_dir_all(dir)
                 .await
-                .map_err(|_| SaveError::File)?;
+                .map_err(|error| SaveError::File(FileError::new(dir, &error)))?;
         }
 
+        // write next to the config and swap it in, so a crash mid-write
+        // leaves the old config intact
+        let temp_path = path.with_extension("json.tmp");
         {
-            let mut file = async_std::fs::File::create(path)
-                .await
-                .map_err(|_| SaveError::File)?;
+            let write_error = |error| SaveError::Write(FileError::new(&temp_path, &error));
 
-            file.write_all(json.as_bytes())
+            let mut file = async_std::fs::File::create(&temp_path)
                 .await
-                .map_err(|_| SaveError::Write)?;
+                .map_err(|error| SaveError::File(FileError::new(&temp_path, &error)))?;
+
+            file.write_all(json.as_bytes()).await.map_err(write_error)?;
+            file.sync_all().await.map_err(write_error)?;
         }
 
+        if let Err(error) = rotate_backups(&path, &profile) {
+            println!("Could not back up the config: {}", error);
+        }
+
+        async_std::fs::rename(&temp_path, &path)
+            .await
+            .map_err(|error| SaveError::File(FileError::new(&path, &error)))?;
+
         Ok(())
     }
 }
+
+/// Copies a config that could not be loaded next to it, so saving the
+/// current state does not lose it. Returns where the copy went.
+fn backup_unreadable(path: &std::path::Path, profile: &str) -> Option<PathBuf> {
+    let backup = backup_dir().join(format!(
+        "{}-unreadable-{}.json",
+        backup_prefix(profile),
+        crate::history::now()
+    ));
+
+    match std::fs::create_dir_all(backup_dir()).and_then(|_| std::fs::copy(path, &backup)) {
+        Ok(_) => Some(backup),
+        Err(error) => {
+            println!("Could not back up {}: {}", path.display(), error);
+            None
+        }
+    }
+}
+
+/// Configs from before versioning. Back then a tab was only
+/// `{ "name", "directory" }`, which still reads as a directory tab, and every
+/// field added since has a default. The volume was saved as it was heard
+/// though, so a muted board has a volume of 0, which becomes a turned off
+/// volume at its full level. A turned off speed was saved as 1, the same as
+/// normal speed, so there is nothing to tell apart there.
+fn migrate_unversioned(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    if object.get("global_volume").and_then(Value::as_f64) != Some(0.0) {
+        return;
+    }
+
+    object.insert("global_volume".to_owned(), 1.0.into());
+    let session = object
+        .entry("session")
+        .or_insert_with(|| Value::Object(Default::default()));
+    if let Some(session) = session.as_object_mut() {
+        session.insert("volume_enabled".to_owned(), false.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::TabKind;
+    use std::path::Path;
+
+    const UNVERSIONED: &str = r#"{
+        "tabs": [{ "name": "Memes", "directory": "/sounds/memes" }],
+        "current_tab": 0,
+        "global_volume": 0.0,
+        "global_speed": 1.5
+    }"#;
+
+    fn parse(contents: &str) -> Result<SavedState, LoadError> {
+        SavedState::parse(Path::new("config.json"), contents)
+    }
+
+    #[test]
+    fn unversioned_config_is_migrated() {
+        let state = parse(UNVERSIONED).unwrap();
+
+        assert_eq!(state.version, VERSION);
+        assert_eq!(state.tabs.len(), 1);
+        assert_eq!(state.tabs[0].name, "Memes");
+        assert_eq!(
+            state.tabs[0].kind,
+            TabKind::Directory {
+                directory: PathBuf::from("/sounds/memes")
+            }
+        );
+        assert_eq!(state.global_speed, 1.5);
+        assert!(state.session.speed_enabled);
+    }
+
+    #[test]
+    fn unversioned_mute_becomes_turned_off_volume() {
+        let state = parse(UNVERSIONED).unwrap();
+        assert_eq!(state.global_volume, 1.0);
+        assert!(!state.session.volume_enabled);
+
+        let state = parse(&UNVERSIONED.replace("0.0", "0.4")).unwrap();
+        assert_eq!(state.global_volume, 0.4);
+        assert!(state.session.volume_enabled);
+    }
+
+    #[test]
+    fn current_config_is_left_as_is() {
+        let contents = UNVERSIONED.replace("\"current_tab\"", "\"version\": 1, \"current_tab\"");
+        let state = parse(&contents).unwrap();
+
+        assert_eq!(state.global_volume, 0.0);
+        assert!(state.session.volume_enabled);
+    }
+
+    #[test]
+    fn newer_config_is_refused() {
+        let contents = UNVERSIONED.replace(
+            "\"current_tab\"",
+            &format!("\"version\": {}, \"current_tab\"", VERSION + 1),
+        );
+
+        assert!(matches!(
+            parse(&contents),
+            Err(LoadError::Version { version, .. }) if version == u64::from(VERSION) + 1
+        ));
+    }
+
+    #[test]
+    fn huge_version_is_refused() {
+        let huge = u64::from(u32::MAX) + 1;
+        let contents = UNVERSIONED.replace(
+            "\"current_tab\"",
+            &format!("\"version\": {huge}, \"current_tab\""),
+        );
+
+        assert!(matches!(
+            parse(&contents),
+            Err(LoadError::Version { version, .. }) if version == huge
+        ));
+    }
+
+    #[test]
+    fn broken_config_reports_where() {
+        assert!(matches!(
+            parse("{ \"tabs\": [ }"),
+            Err(LoadError::Format { .. })
+        ));
+    }
+}
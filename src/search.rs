@@ -0,0 +1,148 @@
+use crate::{app::Tab, audio::AudioClip};
+
+use std::collections::BTreeSet;
+
+const SCORE_MATCH: i32 = 1;
+const SCORE_CONSECUTIVE: i32 = 5;
+const SCORE_WORD_START: i32 = 8;
+const SCORE_SUBSTRING: i32 = 16;
+
+/// Scores how well `query` fuzzily matches `candidate`.
+///
+/// Every character of the query has to appear in the candidate in order, with
+/// consecutive runs, word starts and plain substrings ranked higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query = query.to_lowercase();
+    let candidate = candidate.to_lowercase();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut query_chars = query.chars().peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in candidate.chars() {
+        match query_chars.peek() {
+            Some(&q) if q == c => {
+                score += SCORE_MATCH;
+                if previous_matched {
+                    score += SCORE_CONSECUTIVE;
+                }
+                if !previous.is_some_and(char::is_alphanumeric) {
+                    score += SCORE_WORD_START;
+                }
+
+                query_chars.next();
+                previous_matched = true;
+            }
+            Some(_) => previous_matched = false,
+            None => break,
+        }
+        previous = Some(c);
+    }
+
+    if query_chars.peek().is_some() {
+        return None;
+    }
+
+    if candidate.contains(&query) {
+        score += SCORE_SUBSTRING;
+    }
+
+    Some(score)
+}
+
+/// Scores a clip against every whitespace separated word of the query.
+///
+/// Each word is matched against the file name, the tag fields, the user tags,
+/// the folder the file lives in and the name of its tab; all words have to
+/// match somewhere.
+pub fn score_clip(
+    query: &str,
+    tab: &Tab,
+    clip: &AudioClip,
+    tags: Option<&BTreeSet<String>>,
+) -> Option<i32> {
+    let folder = clip
+        .path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let fields = [
+        Some(clip.name.as_str()),
+        clip.title.as_deref(),
+        clip.artist.as_deref(),
+        clip.album.as_deref(),
+        clip.comment.as_deref(),
+        Some(tab.name.as_str()),
+        Some(folder.as_ref()),
+    ];
+    let tags = tags.into_iter().flatten().map(String::as_str);
+
+    query.split_whitespace().try_fold(0, |total, word| {
+        fields
+            .iter()
+            .flatten()
+            .copied()
+            .chain(tags.clone())
+            .filter_map(|field| fuzzy_score(word, field))
+            .max()
+            .map(|score| total + score)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{path::PathBuf, time::Duration};
+
+    fn clip(name: &str) -> AudioClip {
+        AudioClip {
+            name: name.to_owned(),
+            path: PathBuf::from("/sounds/memes").join(name),
+            duration: Duration::from_secs(1),
+            title: None,
+            artist: None,
+            album: None,
+            comment: None,
+            cover: None,
+            modified: None,
+            added: None,
+        }
+    }
+
+    #[test]
+    fn prefixes_and_word_starts_rank_first() {
+        let score = |candidate| fuzzy_score("air", candidate).unwrap();
+
+        assert!(score("airhorn") > score("chair"));
+        assert!(score("big airhorn") > score("chair"));
+        assert!(score("chair") > score("a big room"));
+        assert!(fuzzy_score("horn", "air horn") > fuzzy_score("horn", "shorn"));
+    }
+
+    #[test]
+    fn letters_have_to_come_in_order() {
+        assert!(fuzzy_score("ahn", "airhorn").is_some());
+        assert_eq!(fuzzy_score("nra", "airhorn"), None);
+        assert_eq!(fuzzy_score("AIR", "Airhorn"), fuzzy_score("air", "airhorn"));
+        assert_eq!(fuzzy_score("", "airhorn"), Some(0));
+    }
+
+    #[test]
+    fn every_word_has_to_match_somewhere() {
+        let tab = Tab::new_collection("Favorites".to_owned());
+        let airhorn = clip("airhorn.mp3");
+        let tags = BTreeSet::from(["loud".to_owned()]);
+
+        assert!(score_clip("air memes", &tab, &airhorn, None).is_some());
+        assert!(score_clip("horn fav", &tab, &airhorn, None).is_some());
+        assert!(score_clip("air loud", &tab, &airhorn, Some(&tags)).is_some());
+        assert_eq!(score_clip("air loud", &tab, &airhorn, None), None);
+        assert_eq!(score_clip("air bruh", &tab, &airhorn, None), None);
+    }
+}
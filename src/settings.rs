@@ -0,0 +1,756 @@
+//! The hand-editable part of the config: tabs, volume, speed and key
+//! bindings, kept in a TOML file next to the JSON config. The running app
+//! picks up edits to it, and writes its own changes back without touching
+//! the comments and layout around them.
+
+use crate::{
+    app::{Message, TabKind},
+    saving::{FileError, FormatError},
+};
+
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+use toml_edit::{Array, ArrayOfTables, DocumentMut, ImDocument, Item, Table, TableLike, Value};
+
+/// What a new settings file starts out as, before the app fills in its values.
+const TEMPLATE: &str = r#"# Soundboard settings
+#
+# Changes to this file are picked up while the app is running. Everything
+# not listed here is kept in the JSON config next to it.
+#
+# Tabs are listed at the end, each with a name and one of
+#   directory = "<folder>"
+#   collection = ["<file>", ...]
+#   query = "<smart tab query>"
+
+# Volume from 0 to 1, and whether it is turned on.
+volume = 1.0
+volume_enabled = true
+# Playback speed, where 1 is normal, and whether it is turned on.
+speed = 1.0
+speed_enabled = true
+
+# Keys for the app's actions, written like "Ctrl+Shift+K" or "Escape". An
+# action can have a list of keys, and an empty list turns it off.
+[keys]
+"#;
+
+/// Something a key binding can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    FocusSearch,
+    ClearSearch,
+    NextTab,
+    PreviousTab,
+    /// Selects the tab at the index.
+    SelectTab(usize),
+    PlayRandom,
+    StopAll,
+    ToggleVolume,
+    Undo,
+    Redo,
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        [
+            Action::FocusSearch,
+            Action::ClearSearch,
+            Action::NextTab,
+            Action::PreviousTab,
+        ]
+        .into_iter()
+        .chain((0..9).map(Action::SelectTab))
+        .chain([
+            Action::PlayRandom,
+            Action::StopAll,
+            Action::ToggleVolume,
+            Action::Undo,
+            Action::Redo,
+        ])
+        .collect()
+    }
+
+    fn name(self) -> String {
+        match self {
+            Action::FocusSearch => "focus_search".to_owned(),
+            Action::ClearSearch => "clear_search".to_owned(),
+            Action::NextTab => "next_tab".to_owned(),
+            Action::PreviousTab => "previous_tab".to_owned(),
+            Action::SelectTab(index) => format!("tab_{}", index + 1),
+            Action::PlayRandom => "play_random".to_owned(),
+            Action::StopAll => "stop_all".to_owned(),
+            Action::ToggleVolume => "toggle_volume".to_owned(),
+            Action::Undo => "undo".to_owned(),
+            Action::Redo => "redo".to_owned(),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::all()
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+
+    pub fn message(self) -> Message {
+        match self {
+            Action::FocusSearch => Message::FocusSearch,
+            Action::ClearSearch => Message::ClearSearch,
+            Action::NextTab => Message::CycleTab(1),
+            Action::PreviousTab => Message::CycleTab(-1),
+            Action::SelectTab(index) => Message::SelectTab(index),
+            Action::PlayRandom => Message::PlayRandom,
+            Action::StopAll => Message::StopAllPlaybacks,
+            Action::ToggleVolume => Message::VolumeToggled,
+            Action::Undo => Message::Undo,
+            Action::Redo => Message::Redo,
+        }
+    }
+
+    fn default_keys(self) -> Vec<String> {
+        let command = if cfg!(target_os = "macos") {
+            "Super"
+        } else {
+            "Ctrl"
+        };
+
+        match self {
+            Action::FocusSearch => vec![format!("{command}+F")],
+            Action::ClearSearch => vec!["Escape".to_owned()],
+            Action::NextTab => vec![format!("{command}+Tab")],
+            Action::PreviousTab => vec![format!("{command}+Shift+Tab")],
+            Action::SelectTab(index) => vec![format!("{command}+{}", index + 1)],
+            Action::Undo => vec![format!("{command}+Z")],
+            Action::Redo => vec![format!("{command}+Shift+Z")],
+            Action::PlayRandom | Action::StopAll | Action::ToggleVolume => vec![],
+        }
+    }
+}
+
+/// Returns the keys every action has when the settings don't say otherwise.
+pub fn default_keys() -> BTreeMap<Action, Vec<String>> {
+    Action::all()
+        .into_iter()
+        .map(|action| (action, action.default_keys()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub volume: f32,
+    pub volume_enabled: bool,
+    pub speed: f64,
+    pub speed_enabled: bool,
+    pub keys: BTreeMap<Action, Vec<String>>,
+    /// The name and contents of every tab, in order.
+    pub tabs: Vec<(String, TabKind)>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            volume_enabled: true,
+            speed: 1.0,
+            speed_enabled: true,
+            keys: default_keys(),
+            tabs: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingsError {
+    File(FileError),
+    Format { path: PathBuf, error: FormatError },
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::File(error) => write!(f, "{}", error),
+            SettingsError::Format { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+/// Returns a hash of the settings file's contents, or `None` if there is
+/// none. Unlike the modification time, it changes with every edit, even
+/// two made within the same second.
+pub fn fingerprint(path: &Path) -> Option<u64> {
+    let contents = std::fs::read(path).ok()?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    contents.hash(&mut hasher);
+
+    Some(hasher.finish())
+}
+
+/// Reads the settings file, or returns `None` when there is none yet.
+pub fn load(path: &Path) -> Result<Option<Settings>, SettingsError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(SettingsError::File(FileError::new(path, &error))),
+    };
+
+    parse(&text)
+        .map(Some)
+        .map_err(|error| SettingsError::Format {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// Writes the settings into the file, only replacing the values that differ
+/// from what is in it, and returns whether anything was written. A file
+/// that can't be read is left alone for the user to fix.
+pub fn write(path: &Path, settings: &Settings) -> Result<bool, SettingsError> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => return Err(SettingsError::File(FileError::new(path, &error))),
+    };
+    let text = existing.as_deref().unwrap_or(TEMPLATE);
+    let format_error = |error| SettingsError::Format {
+        path: path.to_path_buf(),
+        error,
+    };
+
+    let current = parse(text).map_err(format_error)?;
+    if existing.is_some() && current == *settings {
+        return Ok(false);
+    }
+
+    let mut document: DocumentMut = text
+        .parse()
+        .map_err(|error: toml_edit::TomlError| format_error(toml_error(text, &error)))?;
+    let root = document.as_table_mut();
+
+    if current.volume != settings.volume || existing.is_none() {
+        set(
+            root,
+            "volume",
+            (settings.volume as f64 * 100.0).round() / 100.0,
+        );
+    }
+    if current.volume_enabled != settings.volume_enabled {
+        set(root, "volume_enabled", settings.volume_enabled);
+    }
+    if current.speed != settings.speed || existing.is_none() {
+        set(root, "speed", (settings.speed * 100.0).round() / 100.0);
+    }
+    if current.speed_enabled != settings.speed_enabled {
+        set(root, "speed_enabled", settings.speed_enabled);
+    }
+
+    if !root.contains_key("keys") {
+        root.insert("keys", Item::Table(Table::new()));
+    }
+    if let Some(keys) = root.get_mut("keys").and_then(Item::as_table_like_mut) {
+        for action in Action::all() {
+            let wanted = settings.keys.get(&action).cloned().unwrap_or_default();
+            let written = keys.contains_key(&action.name());
+            if !written || current.keys.get(&action) != Some(&wanted) {
+                let mut value = match wanted.as_slice() {
+                    [key] => Value::from(key.as_str()),
+                    keys => Value::Array(keys.iter().map(String::as_str).collect()),
+                };
+                if let Some(old) = keys.get(&action.name()).and_then(Item::as_value) {
+                    *value.decor_mut() = old.decor().clone();
+                }
+                keys.insert(&action.name(), Item::Value(value));
+            }
+        }
+    }
+
+    if current.tabs != settings.tabs || existing.is_none() {
+        let tabs: ArrayOfTables = settings
+            .tabs
+            .iter()
+            .map(|(name, kind)| {
+                let mut table = Table::new();
+                table.insert("name", toml_edit::value(name.as_str()));
+                match kind {
+                    TabKind::Directory { directory } => {
+                        table.insert("directory", toml_edit::value(path_string(directory)));
+                    }
+                    TabKind::Collection { collection } => {
+                        let files: Array =
+                            collection.iter().map(|path| path_string(path)).collect();
+                        table.insert("collection", toml_edit::value(files));
+                    }
+                    TabKind::Smart { query } => {
+                        table.insert("query", toml_edit::value(query.as_str()));
+                    }
+                }
+                table
+            })
+            .collect();
+        root.insert("tabs", Item::ArrayOfTables(tabs));
+    }
+
+    let text = document.to_string();
+    if existing.as_deref() == Some(text.as_str()) {
+        return Ok(false);
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|error| SettingsError::File(FileError::new(dir, &error)))?;
+    }
+    std::fs::write(path, text)
+        .map_err(|error| SettingsError::File(FileError::new(path, &error)))?;
+
+    Ok(true)
+}
+
+/// Replaces a value, keeping the comment after it.
+fn set(table: &mut Table, key: &str, value: impl Into<Value>) {
+    let mut value = value.into();
+    if let Some(old) = table.get(key).and_then(Item::as_value) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table[key] = Item::Value(value);
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Reads the settings, reporting the first thing that is wrong with them
+/// along with where it is. Anything left out has its default.
+fn parse(text: &str) -> Result<Settings, FormatError> {
+    let document = ImDocument::parse(text).map_err(|error| toml_error(text, &error))?;
+    let root = document.as_table();
+    let invalid = |item: &Item, message: &str| {
+        position(text, item.span().map_or(0, |span| span.start), message)
+    };
+
+    let mut settings = Settings::default();
+    for (key, item) in root.iter() {
+        match key {
+            "volume" => {
+                settings.volume = number(item)
+                    .filter(|volume| (0.0..=1.0).contains(volume))
+                    .ok_or_else(|| invalid(item, "volume must be a number from 0 to 1"))?
+                    as f32;
+            }
+            "speed" => {
+                settings.speed = number(item)
+                    .filter(|speed| (0.0..=2.0).contains(speed))
+                    .ok_or_else(|| invalid(item, "speed must be a number from 0 to 2"))?;
+            }
+            "volume_enabled" | "speed_enabled" => {
+                let enabled = item
+                    .as_bool()
+                    .ok_or_else(|| invalid(item, &format!("{key} must be true or false")))?;
+                if key == "volume_enabled" {
+                    settings.volume_enabled = enabled;
+                } else {
+                    settings.speed_enabled = enabled;
+                }
+            }
+            "keys" => {
+                let keys = item
+                    .as_table_like()
+                    .ok_or_else(|| invalid(item, "keys must be a table"))?;
+                parse_keys(text, keys, &mut settings.keys)?;
+            }
+            "tabs" => {
+                let tabs = item
+                    .as_array_of_tables()
+                    .ok_or_else(|| invalid(item, "tabs must be written as [[tabs]] tables"))?;
+                for tab in tabs.iter() {
+                    settings.tabs.push(parse_tab(text, tab)?);
+                }
+            }
+            _ => {
+                let span = root.key(key).and_then(|key| key.span());
+                return Err(position(
+                    text,
+                    span.map_or(0, |span| span.start),
+                    &format!("unknown setting {key}"),
+                ));
+            }
+        }
+    }
+
+    Ok(settings)
+}
+
+fn parse_keys(
+    text: &str,
+    table: &dyn TableLike,
+    keys: &mut BTreeMap<Action, Vec<String>>,
+) -> Result<(), FormatError> {
+    for (name, item) in table.iter() {
+        let start = |item: &Item| item.span().map_or(0, |span| span.start);
+        let action = Action::from_name(name)
+            .ok_or_else(|| position(text, start(item), &format!("unknown action {name}")))?;
+        let invalid = || {
+            position(
+                text,
+                start(item),
+                &format!("{name} must be a key like \"Ctrl+K\" or a list of them"),
+            )
+        };
+
+        let values: Vec<&str> = match item.as_value() {
+            Some(Value::String(key)) => vec![key.value().as_str()],
+            Some(Value::Array(array)) => array
+                .iter()
+                .map(|value| value.as_str().ok_or_else(invalid))
+                .collect::<Result<_, _>>()?,
+            _ => return Err(invalid()),
+        };
+        let bound = values
+            .into_iter()
+            .map(|key| normalize_key(key).ok_or_else(invalid))
+            .collect::<Result<_, _>>()?;
+        keys.insert(action, bound);
+    }
+
+    Ok(())
+}
+
+fn parse_tab(text: &str, table: &Table) -> Result<(String, TabKind), FormatError> {
+    let start = table.span().map_or(0, |span| span.start);
+    let invalid = |message: &str| position(text, start, message);
+
+    let name = table
+        .get("name")
+        .and_then(Item::as_str)
+        .ok_or_else(|| invalid("every tab needs a name"))?
+        .to_owned();
+
+    let mut kinds = vec![];
+    if let Some(item) = table.get("directory") {
+        // a folder that isn't there right now, like one on a drive that
+        // isn't plugged in, is kept and shows as an empty tab
+        let directory = item
+            .as_str()
+            .ok_or_else(|| invalid("directory must be a path"))?;
+        kinds.push(TabKind::Directory {
+            directory: PathBuf::from(directory),
+        });
+    }
+    if let Some(item) = table.get("collection") {
+        let collection = item
+            .as_array()
+            .and_then(|files| {
+                files
+                    .iter()
+                    .map(|file| file.as_str().map(PathBuf::from))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| invalid("collection must be a list of paths"))?;
+        kinds.push(TabKind::Collection { collection });
+    }
+    if let Some(item) = table.get("query") {
+        let query = item.as_str().ok_or_else(|| invalid("query must be text"))?;
+        kinds.push(TabKind::Smart {
+            query: query.to_owned(),
+        });
+    }
+    if let Some((key, _)) = table
+        .iter()
+        .find(|(key, _)| !["name", "directory", "collection", "query"].contains(key))
+    {
+        return Err(invalid(&format!("tab {name} has an unknown setting {key}")));
+    }
+
+    match <[TabKind; 1]>::try_from(kinds) {
+        Ok([kind]) => Ok((name, kind)),
+        Err(_) => Err(invalid(&format!(
+            "tab {name} needs exactly one of directory, collection or query"
+        ))),
+    }
+}
+
+fn number(item: &Item) -> Option<f64> {
+    item.as_float()
+        .or_else(|| item.as_integer().map(|number| number as f64))
+}
+
+/// Writes a key combination the way pressed keys are named, so "ctrl+shift+k"
+/// becomes "Ctrl+Shift+K".
+fn normalize_key(key: &str) -> Option<String> {
+    let mut modifiers = [false; 4];
+    let mut main = None;
+
+    for part in key.split('+').map(str::trim) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers[0] = true,
+            "alt" | "option" => modifiers[1] = true,
+            "shift" => modifiers[2] = true,
+            "super" | "cmd" | "command" | "meta" | "win" => modifiers[3] = true,
+            "" => return None,
+            _ if main.is_some() => return None,
+            _ => {
+                let mut chars = part.chars();
+                let first = chars.next()?;
+                main = Some(first.to_uppercase().chain(chars).collect::<String>());
+            }
+        }
+    }
+
+    let mut parts: Vec<&str> = ["Ctrl", "Alt", "Shift", "Super"]
+        .into_iter()
+        .zip(modifiers)
+        .filter_map(|(name, pressed)| pressed.then_some(name))
+        .collect();
+    let main = main?;
+    parts.push(&main);
+
+    Some(parts.join("+"))
+}
+
+fn toml_error(text: &str, error: &toml_edit::TomlError) -> FormatError {
+    let start = error.span().map_or(0, |span| span.start);
+    let message = match error.message().trim() {
+        "" => "invalid value",
+        message => message,
+    };
+
+    position(text, start, &message.replace('\n', ", "))
+}
+
+/// Turns an offset into the text into the line and column it is at.
+fn position(text: &str, offset: usize, message: &str) -> FormatError {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+    FormatError {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        message: message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("soundboard-settings-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("settings.toml")
+    }
+
+    #[test]
+    fn reads_values_keys_and_tabs() {
+        let folder = std::env::temp_dir();
+        let text = format!(
+            "volume = 0.5\n\
+             speed_enabled = false\n\
+             [keys]\n\
+             stop_all = \"ctrl+shift+s\"\n\
+             focus_search = []\n\
+             tab_1 = [\"alt+1\", \"F1\"]\n\
+             [[tabs]]\n\
+             name = \"Folder\"\n\
+             directory = {:?}\n\
+             [[tabs]]\n\
+             name = \"Picks\"\n\
+             collection = [\"/a.wav\"]\n\
+             [[tabs]]\n\
+             name = \"Short\"\n\
+             query = \"duration:<5s\"\n",
+            path_string(&folder)
+        );
+        let settings = parse(&text).unwrap();
+
+        assert_eq!(settings.volume, 0.5);
+        assert!(settings.volume_enabled);
+        assert_eq!(settings.speed, 1.0);
+        assert!(!settings.speed_enabled);
+        assert_eq!(settings.keys[&Action::StopAll], ["Ctrl+Shift+S"]);
+        assert!(settings.keys[&Action::FocusSearch].is_empty());
+        assert_eq!(settings.keys[&Action::SelectTab(0)], ["Alt+1", "F1"]);
+        assert_eq!(
+            settings.keys[&Action::ClearSearch],
+            Action::ClearSearch.default_keys()
+        );
+        assert_eq!(
+            settings.tabs,
+            [
+                (
+                    "Folder".to_owned(),
+                    TabKind::Directory { directory: folder }
+                ),
+                (
+                    "Picks".to_owned(),
+                    TabKind::Collection {
+                        collection: vec![PathBuf::from("/a.wav")]
+                    }
+                ),
+                (
+                    "Short".to_owned(),
+                    TabKind::Smart {
+                        query: "duration:<5s".to_owned()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let error = parse("speed = 1\nloudness = 3\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.message, "unknown setting loudness");
+
+        let error = parse("volume = 1\n[keys]\nfly = \"K\"\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 7));
+        assert_eq!(error.message, "unknown action fly");
+
+        let error = parse("volume = 2\n").unwrap_err();
+        assert_eq!(error.line, 1);
+        assert_eq!(error.message, "volume must be a number from 0 to 1");
+
+        let error = parse("[keys]\nundo = \"Ctrl+\"\n").unwrap_err();
+        assert_eq!(error.line, 2);
+
+        let error = parse("[[tabs]]\nname = \"Bad\"\ndirectory = 3\n").unwrap_err();
+        assert_eq!(error.message, "directory must be a path");
+
+        let error =
+            parse("[[tabs]]\nname = \"Both\"\nquery = \"a\"\ncollection = []\n").unwrap_err();
+        assert_eq!(
+            error.message,
+            "tab Both needs exactly one of directory, collection or query"
+        );
+
+        assert!(parse("volume = \n").is_err());
+    }
+
+    #[test]
+    fn missing_folders_are_kept() {
+        let path = temp_file("missing");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "volume = 0.3\n\
+             [keys]\n\
+             stop_all = \"Ctrl+S\"\n\
+             [[tabs]]\n\
+             name = \"Offline\"\n\
+             directory = \"/no/such/drive/sounds\"\n\
+             [[tabs]]\n\
+             name = \"Picks\"\n\
+             collection = [\"/a.wav\"]\n",
+        )
+        .unwrap();
+
+        let settings = load(&path).unwrap().unwrap();
+        assert_eq!(settings.volume, 0.3);
+        assert_eq!(settings.keys[&Action::StopAll], ["Ctrl+S"]);
+        assert_eq!(
+            settings.tabs,
+            [
+                (
+                    "Offline".to_owned(),
+                    TabKind::Directory {
+                        directory: PathBuf::from("/no/such/drive/sounds")
+                    }
+                ),
+                (
+                    "Picks".to_owned(),
+                    TabKind::Collection {
+                        collection: vec![PathBuf::from("/a.wav")]
+                    }
+                ),
+            ]
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keys_are_written_like_pressed_keys() {
+        assert_eq!(
+            normalize_key("ctrl+shift+k").as_deref(),
+            Some("Ctrl+Shift+K")
+        );
+        assert_eq!(
+            normalize_key("cmd + Alt + escape").as_deref(),
+            Some("Alt+Super+Escape")
+        );
+        assert_eq!(normalize_key("F5").as_deref(), Some("F5"));
+        assert_eq!(normalize_key("ctrl"), None);
+        assert_eq!(normalize_key("ctrl+"), None);
+        assert_eq!(normalize_key("a+b"), None);
+    }
+
+    #[test]
+    fn new_files_read_back_the_same() {
+        let path = temp_file("new");
+        let settings = Settings {
+            volume: 0.25,
+            speed_enabled: false,
+            tabs: vec![(
+                "Picks".to_owned(),
+                TabKind::Collection {
+                    collection: vec![PathBuf::from("/a.wav"), PathBuf::from("/b.wav")],
+                },
+            )],
+            ..Settings::default()
+        };
+
+        assert!(write(&path, &settings).unwrap());
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# Soundboard settings"));
+        assert_eq!(load(&path).unwrap(), Some(settings.clone()));
+        assert!(!write(&path, &settings).unwrap());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn writing_keeps_comments_and_layout() {
+        let path = temp_file("comments");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let original = "# mine\n\
+                        volume = 0.5 # loud enough\n\
+                        speed = 1.0\n\
+                        \n\
+                        [keys]\n\
+                        # search\n\
+                        focus_search = \"Ctrl+F\"\n";
+        std::fs::write(&path, original).unwrap();
+
+        let mut settings = load(&path).unwrap().unwrap();
+        settings.volume = 0.75;
+        settings
+            .keys
+            .insert(Action::StopAll, vec!["Ctrl+S".to_owned()]);
+        assert!(write(&path, &settings).unwrap());
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# mine\nvolume = 0.75 # loud enough\nspeed = 1.0\n\n[keys]\n# search\nfocus_search = \"Ctrl+F\"\n"));
+        assert!(text.contains("stop_all = \"Ctrl+S\""));
+        assert!(!text.contains("[[tabs]]"));
+        assert_eq!(load(&path).unwrap(), Some(settings));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn unreadable_files_are_left_alone() {
+        let path = temp_file("broken");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "volume = [\n").unwrap();
+
+        assert!(matches!(
+            write(&path, &Settings::default()),
+            Err(SettingsError::Format { .. })
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "volume = [\n");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use rand::seq::SliceRandom;
+use std::path::{Path, PathBuf};
+
+/// Picks clips in random order without repeating one until every clip was
+/// played, then starts a new round.
+#[derive(Debug, Clone, Default)]
+pub struct Shuffle {
+    remaining: Vec<PathBuf>,
+    last: Option<PathBuf>,
+}
+
+impl Shuffle {
+    /// Returns the next clip out of `candidates`, which may change between
+    /// calls as clips are added or removed.
+    pub fn next(&mut self, candidates: &[&Path]) -> Option<PathBuf> {
+        self.remaining
+            .retain(|path| candidates.contains(&path.as_path()));
+
+        if self.remaining.is_empty() {
+            self.remaining = candidates.iter().map(|path| path.to_path_buf()).collect();
+            self.remaining.shuffle(&mut rand::thread_rng());
+
+            // clips are taken from the back, so keep the last one of the
+            // previous round from coming up again right away
+            let len = self.remaining.len();
+            if len > 1 && self.remaining.last() == self.last.as_ref() {
+                self.remaining.swap(0, len - 1);
+            }
+        }
+
+        let next = self.remaining.pop()?;
+        self.last = Some(next.clone());
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_repeats_until_every_clip_played() {
+        let paths: Vec<PathBuf> = (0..5).map(|n| PathBuf::from(format!("{n}.wav"))).collect();
+        let candidates: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+        let mut shuffle = Shuffle::default();
+        let mut previous = None;
+
+        for _ in 0..20 {
+            let mut round: Vec<PathBuf> = (0..paths.len())
+                .map(|_| shuffle.next(&candidates).unwrap())
+                .collect();
+            // not even across the end of a round
+            assert_ne!(previous.as_ref(), round.first());
+            previous = round.last().cloned();
+
+            round.sort();
+            assert_eq!(round, paths);
+        }
+    }
+
+    #[test]
+    fn single_and_no_clips() {
+        let mut shuffle = Shuffle::default();
+        let only = [Path::new("only.wav")];
+
+        for _ in 0..3 {
+            assert_eq!(shuffle.next(&only), Some(PathBuf::from("only.wav")));
+        }
+        assert_eq!(shuffle.next(&[]), None);
+    }
+
+    #[test]
+    fn removed_clips_are_left_out() {
+        let mut shuffle = Shuffle::default();
+        let (a, b, c) = (Path::new("a.wav"), Path::new("b.wav"), Path::new("c.wav"));
+
+        let first = shuffle.next(&[a, b, c]).unwrap();
+        let left: Vec<&Path> = [a, b, c]
+            .into_iter()
+            .filter(|path| *path != first && *path != b)
+            .collect();
+        let second = shuffle.next(&left).unwrap();
+
+        assert_ne!(second, first);
+        assert_ne!(second, b);
+    }
+}
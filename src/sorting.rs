@@ -0,0 +1,202 @@
+use crate::{audio::AudioClip, history::PlayStats};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortMode {
+    #[default]
+    Name,
+    Duration,
+    Modified,
+    Added,
+    PlayCount,
+    Manual,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 6] = [
+        SortMode::Name,
+        SortMode::Duration,
+        SortMode::Modified,
+        SortMode::Added,
+        SortMode::PlayCount,
+        SortMode::Manual,
+    ];
+}
+
+impl std::fmt::Display for SortMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortMode::Name => "Name",
+            SortMode::Duration => "Duration",
+            SortMode::Modified => "Date modified",
+            SortMode::Added => "Date added",
+            SortMode::PlayCount => "Play count",
+            SortMode::Manual => "Manual",
+        })
+    }
+}
+
+/// Compares strings the way people expect, treating runs of digits as numbers
+/// so "clip2" comes before "clip10".
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a);
+                let y = take_number(&mut b);
+
+                // compare by magnitude first, then by leading zeros
+                let ordering = x
+                    .trim_start_matches('0')
+                    .len()
+                    .cmp(&y.trim_start_matches('0').len())
+                    .then_with(|| x.trim_start_matches('0').cmp(y.trim_start_matches('0')))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x
+                    .to_lowercase()
+                    .cmp(y.to_lowercase())
+                    .then_with(|| x.cmp(&y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        number.push(c);
+    }
+    number
+}
+
+/// Sorts `(tab index, clip)` pairs in place. Newest and most played clips come
+/// first, everything else is ascending; ties fall back to the file name.
+pub fn sort_clips(
+    clips: &mut [(usize, &AudioClip)],
+    mode: SortMode,
+    play_stats: &PlayStats,
+    manual_order: &[PathBuf],
+) {
+    let play_count = |clip: &AudioClip| {
+        play_stats
+            .get(&clip.path)
+            .map_or(0, |stats| stats.play_count)
+    };
+    let positions: HashMap<&Path, usize> = match mode {
+        SortMode::Manual => manual_order
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, path)| (path.as_path(), index))
+            .collect(),
+        _ => HashMap::new(),
+    };
+    let position = |clip: &AudioClip| {
+        positions
+            .get(clip.path.as_path())
+            .copied()
+            .unwrap_or(usize::MAX)
+    };
+
+    clips.sort_by(|(_, a), (_, b)| {
+        let ordering = match mode {
+            SortMode::Name => Ordering::Equal,
+            SortMode::Duration => a.duration.cmp(&b.duration),
+            SortMode::Modified => b.modified.cmp(&a.modified),
+            SortMode::Added => b.added.cmp(&a.added),
+            SortMode::PlayCount => play_count(b).cmp(&play_count(a)),
+            SortMode::Manual => position(a).cmp(&position(b)),
+        };
+
+        ordering.then_with(|| natural_cmp(&a.name, &b.name))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn clip(name: &str, seconds: u64) -> AudioClip {
+        AudioClip {
+            name: name.to_owned(),
+            path: PathBuf::from(format!("/clips/{name}.wav")),
+            duration: Duration::from_secs(seconds),
+            title: None,
+            artist: None,
+            album: None,
+            comment: None,
+            cover: None,
+            modified: None,
+            added: None,
+        }
+    }
+
+    fn sorted(clips: &[AudioClip], mode: SortMode, manual_order: &[PathBuf]) -> Vec<String> {
+        let mut pairs: Vec<_> = clips.iter().enumerate().collect();
+        sort_clips(&mut pairs, mode, &PlayStats::new(), manual_order);
+        pairs
+            .into_iter()
+            .map(|(_, clip)| clip.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(natural_cmp("clip2", "clip10"), Ordering::Less);
+        assert_eq!(natural_cmp("clip10", "clip9"), Ordering::Greater);
+        assert_eq!(natural_cmp("clip007", "clip7"), Ordering::Greater);
+        assert_eq!(natural_cmp("clip", "clip1"), Ordering::Less);
+        assert_eq!(natural_cmp("a1b2", "a1b2"), Ordering::Equal);
+    }
+
+    #[test]
+    fn letters_ignore_case_before_breaking_ties() {
+        assert_eq!(natural_cmp("apple", "Banana"), Ordering::Less);
+        assert_eq!(natural_cmp("Apple", "apple"), Ordering::Less);
+    }
+
+    #[test]
+    fn ties_fall_back_to_the_name() {
+        let clips = [clip("b", 3), clip("a10", 1), clip("a2", 1)];
+        assert_eq!(sorted(&clips, SortMode::Name, &[]), ["a2", "a10", "b"]);
+        assert_eq!(sorted(&clips, SortMode::Duration, &[]), ["a2", "a10", "b"]);
+    }
+
+    #[test]
+    fn manual_order_puts_unlisted_clips_last() {
+        let clips = [clip("a", 1), clip("b", 1), clip("c", 1), clip("d", 1)];
+        let order = [
+            PathBuf::from("/clips/c.wav"),
+            PathBuf::from("/clips/a.wav"),
+            PathBuf::from("/clips/gone.wav"),
+            PathBuf::from("/clips/c.wav"),
+        ];
+        assert_eq!(
+            sorted(&clips, SortMode::Manual, &order),
+            ["c", "a", "b", "d"]
+        );
+    }
+}
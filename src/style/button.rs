@@ -26,6 +26,8 @@ enum CustomButtonStyle {
     Active,
     Toolbar,
     Tab(bool),
+    Swatch(bool),
+    Pad(bool),
 }
 
 #[derive(Default)]
@@ -60,6 +62,24 @@ impl CustomButton {
         })
     }
 
+    /// A solid button filled with its color, outlined when `selected`.
+    pub fn swatch(color: iced::Color, selected: bool) -> Self {
+        Self(CustomButtonState {
+            custom_style: CustomButtonStyle::Swatch(selected),
+            color: Some(color),
+            ..Default::default()
+        })
+    }
+
+    /// A pad tinted with its color, if any, outlined when `highlighted`.
+    pub fn pad(color: Option<iced::Color>, highlighted: bool) -> Self {
+        Self(CustomButtonState {
+            custom_style: CustomButtonStyle::Pad(highlighted),
+            color,
+            ..Default::default()
+        })
+    }
+
     pub fn with_color(mut self, color: iced::Color) -> Self {
         self.0.color = Some(color);
         self
@@ -119,6 +139,31 @@ impl iced::widget::button::StyleSheet for CustomButton {
                     text_color: style.extended_palette().primary.strong.color,
                     ..Default::default()
                 },
+                CustomButtonStyle::Swatch(selected) => iced::widget::button::Appearance {
+                    background: color.map(Into::into),
+                    text_color: style.palette().text,
+                    border: Border {
+                        color: style.palette().text,
+                        width: if *selected { 2.0 } else { 0.0 },
+                        radius: Radius::from(*border_radius),
+                    },
+                    ..Default::default()
+                },
+                CustomButtonStyle::Pad(highlighted) => {
+                    let mut background = color.unwrap_or(style.palette().primary);
+                    background.a = if color.is_some() { 0.35 } else { 0.05 };
+
+                    iced::widget::button::Appearance {
+                        background: Some(background.into()),
+                        text_color: style.palette().text,
+                        border: Border {
+                            color: style.palette().text,
+                            width: if *highlighted { 2.0 } else { 0.0 },
+                            radius: Radius::from(*border_radius),
+                        },
+                        ..Default::default()
+                    }
+                }
                 CustomButtonStyle::Tab(active) => {
                     let mut background = style.palette().primary;
                     background.a = if *active { 0.05 } else { 0.025 };
@@ -178,6 +223,23 @@ impl iced::widget::button::StyleSheet for CustomButton {
                     text_color: style.extended_palette().primary.base.color,
                     ..Default::default()
                 },
+                CustomButtonStyle::Swatch(_) => iced::widget::button::Appearance {
+                    border: Border {
+                        color: style.palette().text,
+                        width: 2.0,
+                        radius: Radius::from(*border_radius),
+                    },
+                    ..self.active(style)
+                },
+                CustomButtonStyle::Pad(_) => {
+                    let mut background = color.unwrap_or(style.palette().primary);
+                    background.a = if color.is_some() { 0.5 } else { 0.125 };
+
+                    iced::widget::button::Appearance {
+                        background: Some(background.into()),
+                        ..self.active(style)
+                    }
+                }
                 CustomButtonStyle::Tab(_) => {
                     let mut background = style.palette().primary;
                     background.a = 0.125;
@@ -2,7 +2,6 @@ use iced::{border::Radius, Border};
 
 use super::BORDER_RADIUS;
 
-#[allow(unused)]
 struct CustomContainerState {
     custom_style: CustomContainerStyle,
     border_radius: [f32; 4],
@@ -21,12 +20,28 @@ impl Default for CustomContainerState {
 enum CustomContainerStyle {
     #[default]
     Default,
+    Tooltip,
+    Swatch(iced::Color),
 }
 
 #[derive(Default)]
 pub struct CustomContainer(CustomContainerState);
 
 impl CustomContainer {
+    pub fn tooltip() -> Self {
+        Self(CustomContainerState {
+            custom_style: CustomContainerStyle::Tooltip,
+            ..Default::default()
+        })
+    }
+
+    pub fn swatch(color: iced::Color) -> Self {
+        Self(CustomContainerState {
+            custom_style: CustomContainerStyle::Swatch(color),
+            ..Default::default()
+        })
+    }
+
     pub fn with_border_radius(mut self, radius: [f32; 4]) -> Self {
         self.0.border_radius = radius.into();
         self
@@ -45,19 +60,39 @@ impl iced::widget::container::StyleSheet for CustomContainer {
     fn appearance(&self, style: &Self::Style) -> iced::widget::container::Appearance {
         match &self.0 {
             CustomContainerState {
-                custom_style: _,
+                custom_style,
                 border_radius,
             } => {
                 let mut background = style.palette().primary;
                 background.a = 0.05;
 
-                iced::widget::container::Appearance {
-                    background: Some(background.into()),
-                    border: Border {
-                        radius: Radius::from(*border_radius),
+                match custom_style {
+                    CustomContainerStyle::Default => iced::widget::container::Appearance {
+                        background: Some(background.into()),
+                        border: Border {
+                            radius: Radius::from(*border_radius),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    CustomContainerStyle::Swatch(color) => iced::widget::container::Appearance {
+                        background: Some((*color).into()),
+                        border: Border {
+                            radius: Radius::from(*border_radius),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    CustomContainerStyle::Tooltip => iced::widget::container::Appearance {
+                        background: Some(style.palette().background.into()),
+                        text_color: Some(style.palette().text),
+                        border: Border {
+                            color: background,
+                            width: 1.0,
+                            radius: Radius::from(*border_radius),
+                        },
                         ..Default::default()
                     },
-                    ..Default::default()
                 }
             }
         }
@@ -75,6 +75,10 @@ pub fn plus<'a>() -> Element<'a, Message> {
     icon("list-add-symbolic").into()
 }
 
+pub fn document_new<'a>() -> Element<'a, Message> {
+    icon("document-new-symbolic").into()
+}
+
 pub fn refresh<'a>() -> Element<'a, Message> {
     icon("view-refresh-symbolic").into()
 }
@@ -88,6 +92,54 @@ pub fn speed<'a>() -> Element<'a, Message> {
     icon("power-profile-performance-symbolic").into()
 }
 
+pub fn starred<'a>() -> Element<'a, Message> {
+    icon("starred-symbolic").into()
+}
+
+pub fn non_starred<'a>() -> Element<'a, Message> {
+    icon("non-starred-symbolic").into()
+}
+
+pub fn tag<'a>() -> Element<'a, Message> {
+    icon("tag-symbolic").into()
+}
+
+pub fn view_sort<'a>() -> Element<'a, Message> {
+    icon("view-sort-ascending-symbolic").into()
+}
+
+pub fn view_grid<'a>() -> Element<'a, Message> {
+    icon("view-grid-symbolic").into()
+}
+
+pub fn view_list<'a>() -> Element<'a, Message> {
+    icon("view-list-symbolic").into()
+}
+
+pub fn drag_handle<'a>() -> Element<'a, Message> {
+    icon("list-drag-handle-symbolic").into()
+}
+
+pub fn history<'a>() -> Element<'a, Message> {
+    icon("document-open-recent-symbolic").into()
+}
+
+pub fn duplicates<'a>() -> Element<'a, Message> {
+    icon("edit-copy-symbolic").into()
+}
+
+pub fn warning<'a>() -> Element<'a, Message> {
+    icon("emblem-important-symbolic").into()
+}
+
+pub fn shuffle<'a>() -> Element<'a, Message> {
+    icon("media-playlist-shuffle-symbolic").into()
+}
+
+pub fn pan_down<'a>() -> Element<'a, Message> {
+    icon("pan-down-symbolic").into()
+}
+
 pub fn cancel<'a>() -> Element<'a, Message> {
     icon("window-close-symbolic").into()
 }
@@ -15,6 +15,7 @@ pub const FONT_BYTES_BOLD: &[u8] = include_bytes!("../../assets/fonts/Roboto/Rob
 pub const ICON_DIR: Dir = include_dir!("assets/adwaita-icons/");
 
 pub const FONT_NAME: &'static str = "Roboto";
+pub const FONT_SIZE_SMALL: u16 = 12;
 pub const FONT_SIZE_DEFAULT: u16 = 16;
 pub const FONT_SIZE_TABS: u16 = 18;
 
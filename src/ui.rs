@@ -1,8 +1,17 @@
 use crate::{
-    app::{AppState, Message, SoundboardApp, Tab},
-    audio::AudioCommand,
-    saving::SavedState,
-    style::{self, icons, BORDER_RADIUS, FONT_NAME, FONT_SIZE_TABS, SPACING_NORMAL, SPACING_SMALL},
+    app::{AppState, ClipLayout, FileEdit, FileOperation, Message, SoundboardApp, Tab, TabKind},
+    audio::{AudioClip, AudioCommand, ClipColor, ClipSettings},
+    duplicates,
+    files::{self, ImportMode},
+    history::{self, ReportFormat},
+    importers, pack,
+    query::Query,
+    saving::{self, LoadError, SavedState},
+    sorting::SortMode,
+    style::{
+        self, icons, BORDER_RADIUS, FONT_NAME, FONT_SIZE_SMALL, FONT_SIZE_TABS, SPACING_NORMAL,
+        SPACING_SMALL,
+    },
 };
 
 use iced::{
@@ -11,12 +20,25 @@ use iced::{
         self,
         scrollable::{Direction, Properties},
     },
-    Alignment, Command, Element, Font, Length,
+    window, Alignment, Command, Element, Font, Length,
 };
+use iced_aw::{ContextMenu, DropDown};
 use kira::sound::PlaybackState;
 
 const TOOL_BUTTON_SIZE: Length = Length::Fixed(26.0);
 const TOOL_BUTTON_SIZE_SMALL: Length = Length::Fixed(24.0);
+const TAB_HEIGHT: Length = Length::Fixed(34.0);
+const COVER_SIZE: Length = Length::Fixed(32.0);
+const MENU_WIDTH: Length = Length::Fixed(200.0);
+const SWATCH_SIZE: f32 = 18.0;
+const PAD_SIZE_MIN: f32 = 64.0;
+const SCROLLBAR_SPACE: f32 = 8.0;
+const PROGRESS_RING_SIZE: Length = Length::Fixed(20.0);
+const PROGRESS_RING_WIDTH: f32 = 3.0;
+const GRID_COLUMNS: std::ops::RangeInclusive<u16> = 2..=8;
+const RECENT_PLAYS_LIMIT: usize = 50;
+const TAB_RENAME_WIDTH: Length = Length::Fixed(140.0);
+const TAB_MENU_HEIGHT: f32 = 300.0;
 
 #[allow(unused)]
 impl SoundboardApp {
@@ -49,15 +71,35 @@ impl SoundboardApp {
     pub fn view_full(&self) -> Element<Message> {
         match self {
             Self::Loaded(state) => {
-                let tab_bar = self.view_tab_bar();
+                let tab_bar = widget::mouse_area(self.view_tab_bar())
+                    .on_enter(Message::TabBarHovered(true))
+                    .on_exit(Message::TabBarHovered(false));
                 let content = self.view_content();
-                let content_column = widget::column!(tab_bar, content).height(Length::Fill);
+                let search_bar = self.view_search_bar();
+                let content_column = widget::Column::new()
+                    .push(tab_bar)
+                    .push_maybe(self.view_closed_tab_bar())
+                    .push(content)
+                    .height(Length::Fill);
 
                 let controls = self.view_controls();
                 let playbacks = self.view_playbacks();
 
                 let mut column_widgets = vec![];
+                if let Some(notifications) = self.view_notifications() {
+                    column_widgets.push(notifications);
+                }
+                column_widgets.push(search_bar);
+                if let Some(editor) = self.view_profile_editor() {
+                    column_widgets.push(editor);
+                }
                 column_widgets.push(content_column.into());
+                if state.history_open {
+                    column_widgets.push(self.view_history());
+                }
+                if state.duplicates_open {
+                    column_widgets.push(self.view_duplicates());
+                }
                 if !state.active_playbacks.is_empty() {
                     column_widgets.push(playbacks.into());
                 }
@@ -77,6 +119,53 @@ impl SoundboardApp {
         }
     }
 
+    /// Shows errors and other notices until they are dismissed, newest last.
+    fn view_notifications(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                if state.notifications.is_empty() {
+                    return None;
+                }
+
+                let column = state.notifications.iter().enumerate().fold(
+                    widget::Column::new().spacing(SPACING_SMALL),
+                    |column, (idx, notification)| {
+                        let action = notification.action.as_ref().map(|(label, message)| {
+                            widget::button(widget::text(label))
+                                .on_press(message.clone())
+                                .padding([SPACING_SMALL, SPACING_NORMAL])
+                                .style(style::button::CustomButton::default())
+                        });
+
+                        let row = widget::Row::new()
+                            .push(icons::warning())
+                            .push(widget::text(&notification.text).width(Length::Fill))
+                            .push_maybe(action)
+                            .push(
+                                widget::button(icons::cancel())
+                                    .width(TOOL_BUTTON_SIZE)
+                                    .height(TOOL_BUTTON_SIZE)
+                                    .on_press(Message::DismissNotification(idx))
+                                    .style(style::button::CustomButton::flat()),
+                            )
+                            .spacing(SPACING_NORMAL)
+                            .align_items(Alignment::Center);
+
+                        column.push(
+                            widget::container(row)
+                                .width(Length::Fill)
+                                .padding([SPACING_SMALL, SPACING_NORMAL])
+                                .style(style::container::CustomContainer::default()),
+                        )
+                    },
+                );
+
+                Some(column.into())
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
     fn view_tab_bar(&self) -> Element<Message> {
         match self {
             Self::Loaded(state) => {
@@ -86,56 +175,146 @@ impl SoundboardApp {
                     .iter()
                     .enumerate()
                     .fold(widget::Row::new(), |row, (idx, tab)| {
-                        let text = widget::text(tab.name.to_owned()).size(FONT_SIZE_TABS);
+                        let label: Element<Message> = match &state.tab_rename {
+                            Some((index, name)) if *index == idx => {
+                                widget::text_input(&tab.name, name)
+                                    .id(tab_rename_input_id())
+                                    .on_input(Message::TabRenameChanged)
+                                    .on_submit(Message::SubmitTabRename)
+                                    .size(FONT_SIZE_TABS)
+                                    .width(TAB_RENAME_WIDTH)
+                                    .into()
+                            }
+                            // pressing the name selects the tab and starts dragging it
+                            _ => widget::mouse_area(
+                                widget::text(tab.name.to_owned()).size(FONT_SIZE_TABS),
+                            )
+                            .on_press(Message::TabDragStart(idx))
+                            .into(),
+                        };
                         let close_button = widget::button(icons::cancel())
                             .width(TOOL_BUTTON_SIZE_SMALL)
                             .height(TOOL_BUTTON_SIZE_SMALL)
                             .on_press(Message::CloseTab(idx))
                             .style(style::button::CustomButton::flat());
 
+                        let is_drag_target = state.tab_dragging.is_some_and(|from| from != idx)
+                            && state.tab_drag_target == Some(idx);
                         let button = widget::button(
-                            widget::row!(text, close_button)
+                            widget::row!(label, close_button)
                                 .spacing(SPACING_NORMAL)
                                 .align_items(Alignment::Center),
                         )
+                        .height(TAB_HEIGHT)
                         .padding([SPACING_SMALL, SPACING_NORMAL])
                         .on_press(Message::SelectTab(idx))
                         .style(
-                            style::button::CustomButton::tab(state.current_tab == idx)
-                                .with_border_radius([
-                                    if idx == 0 { BORDER_RADIUS } else { 0.0 },
-                                    if idx == len - 1 { BORDER_RADIUS } else { 0.0 },
-                                    0.0,
-                                    0.0,
-                                ]), // .with_border_radius(if len == 1 {
-                                    //     [BORDER_RADIUS, BORDER_RADIUS, 0.0, 0.0]
-                                    // } else if idx == 0 {
-                                    //     [BORDER_RADIUS, 0.0, 0.0, 0.0]
-                                    // } else if idx == len - 1 {
-                                    //     [0.0, BORDER_RADIUS, 0.0, 0.0]
-                                    // } else {
-                                    //     [0.0; 4]
-                                    // })
+                            style::button::CustomButton::tab(
+                                is_drag_target
+                                    || (!state.favorites_selected && state.current_tab == idx),
+                            )
+                            .with_border_radius([
+                                0.0,
+                                if idx == len - 1 { BORDER_RADIUS } else { 0.0 },
+                                0.0,
+                                0.0,
+                            ]), // .with_border_radius(if len == 1 {
+                                //     [BORDER_RADIUS, BORDER_RADIUS, 0.0, 0.0]
+                                // } else if idx == 0 {
+                                //     [BORDER_RADIUS, 0.0, 0.0, 0.0]
+                                // } else if idx == len - 1 {
+                                //     [0.0, BORDER_RADIUS, 0.0, 0.0]
+                                // } else {
+                                //     [0.0; 4]
+                                // })
                         );
-                        row.push(button)
+
+                        let mut items = vec![
+                            ("Rename", Message::StartTabRename(idx)),
+                            ("Duplicate", Message::DuplicateTab(idx)),
+                        ];
+                        if tab.directory().is_some() {
+                            items.push(("Change folder...", Message::ChangeTabDirectory(idx)));
+                        }
+                        items.push(("Export...", Message::ExportPack(Some(idx))));
+                        items.push(("Close", Message::CloseTab(idx)));
+                        let tab = ContextMenu::new(button, move || view_menu(items.clone()));
+
+                        let mut area =
+                            widget::mouse_area(tab).on_middle_press(Message::CloseTab(idx));
+                        if state.tab_dragging.is_some() {
+                            area = area.on_enter(Message::TabDragOver(idx));
+                        }
+                        row.push(area)
                     })
                     .width(Length::Shrink);
 
+                let favorites_tab = widget::button(
+                    widget::row!(
+                        icons::starred(),
+                        widget::text("Favorites").size(FONT_SIZE_TABS)
+                    )
+                    .spacing(SPACING_SMALL)
+                    .align_items(Alignment::Center),
+                )
+                .height(TAB_HEIGHT)
+                .padding([SPACING_SMALL, SPACING_NORMAL])
+                .on_press(Message::SelectFavorites)
+                .style(
+                    style::button::CustomButton::tab(state.favorites_selected).with_border_radius(
+                        [
+                            BORDER_RADIUS,
+                            if len == 0 { BORDER_RADIUS } else { 0.0 },
+                            0.0,
+                            0.0,
+                        ],
+                    ),
+                );
+
                 let tabs_container = {
                     let scrollable = widget::scrollable(tabs)
                         .direction(Direction::Horizontal(scrollable_properties()))
                         .style(style::scrollable::CustomScrollable::default());
 
-                    let add_button = widget::button(icons::plus())
-                        .on_press(Message::NewTab)
-                        .width(TOOL_BUTTON_SIZE)
-                        .height(TOOL_BUTTON_SIZE)
-                        .style(style::button::CustomButton::default());
+                    let add_button = DropDown::new(
+                        widget::button(icons::plus())
+                            .on_press(Message::ToggleNewTabMenu)
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .style(style::button::CustomButton::default()),
+                        view_menu(vec![
+                            ("Folder...", Message::NewTab),
+                            ("Collection", Message::NewCollection),
+                            ("Smart tab", Message::NewSmartTab),
+                            ("Board pack...", Message::ImportPack),
+                            ("From another app...", Message::ImportBoard),
+                            ("Category folders...", Message::ImportCategories),
+                        ]),
+                        state.new_tab_menu_open,
+                    )
+                    .width(MENU_WIDTH)
+                    .on_dismiss(Message::ToggleNewTabMenu);
 
-                    widget::row!(scrollable, add_button)
-                        .width(Length::Fill)
-                        .spacing(SPACING_NORMAL)
-                        .align_items(Alignment::Center)
+                    let tab_menu = DropDown::new(
+                        widget::button(icons::pan_down())
+                            .on_press(Message::ToggleTabMenu)
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .style(style::button::CustomButton::default()),
+                        self.view_tab_menu(),
+                        state.tab_menu_open,
+                    )
+                    .width(MENU_WIDTH)
+                    .on_dismiss(Message::ToggleTabMenu);
+
+                    widget::row!(
+                        widget::row!(favorites_tab, scrollable).align_items(Alignment::Center),
+                        tab_menu,
+                        add_button
+                    )
+                    .width(Length::Fill)
+                    .spacing(SPACING_NORMAL)
+                    .align_items(Alignment::Center)
                 };
 
                 let buttons_row = {
@@ -145,15 +324,135 @@ impl SoundboardApp {
                     //     .style(style::button::CustomButton::default().into());
                     // // .on_press(Message::ToggleDownloadPopup);
 
+                    let show_metadata = !state.favorites_selected
+                        && state.get_current_tab().is_some_and(|tab| tab.show_metadata);
+                    let metadata_button = widget::button(icons::tag())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press_maybe(
+                            (!state.favorites_selected).then_some(Message::ToggleMetadata),
+                        )
+                        .style(if show_metadata {
+                            style::button::CustomButton::active()
+                        } else {
+                            style::button::CustomButton::default()
+                        });
+
+                    let sort_items = state
+                        .get_current_tab()
+                        .filter(|_| !state.favorites_selected)
+                        .map(|tab| {
+                            SortMode::ALL
+                                .into_iter()
+                                .filter(|mode| *mode != SortMode::Manual || !tab.is_smart())
+                                .map(|mode| {
+                                    let label = if mode == tab.sort {
+                                        format!("• {mode}")
+                                    } else {
+                                        mode.to_string()
+                                    };
+                                    (label, Message::SetSortMode(mode))
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default();
+                    let sort_button = DropDown::new(
+                        widget::button(icons::view_sort())
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .on_press_maybe(
+                                (!sort_items.is_empty()).then_some(Message::ToggleSortMenu),
+                            )
+                            .style(style::button::CustomButton::default()),
+                        view_menu(sort_items),
+                        state.sort_menu_open,
+                    )
+                    .width(MENU_WIDTH)
+                    .on_dismiss(Message::ToggleSortMenu);
+
+                    let current_layout = state
+                        .get_current_tab()
+                        .filter(|_| !state.favorites_selected)
+                        .map(|tab| (tab.layout, tab.grid_columns));
+                    let layout_items = current_layout
+                        .map(|(layout, columns)| {
+                            let mark = |label: String, active: bool| {
+                                if active {
+                                    format!("• {label}")
+                                } else {
+                                    label
+                                }
+                            };
+
+                            let mut items = vec![
+                                (
+                                    mark("List".to_owned(), layout == ClipLayout::List),
+                                    Message::SetLayout(ClipLayout::List),
+                                ),
+                                (
+                                    mark("Grid".to_owned(), layout == ClipLayout::Grid),
+                                    Message::SetLayout(ClipLayout::Grid),
+                                ),
+                            ];
+                            if layout == ClipLayout::Grid {
+                                items.extend(GRID_COLUMNS.map(|count| {
+                                    (
+                                        mark(format!("{count} columns"), count == columns),
+                                        Message::SetGridColumns(count),
+                                    )
+                                }));
+                            }
+
+                            items
+                        })
+                        .unwrap_or_default();
+                    let layout_button = DropDown::new(
+                        widget::button(
+                            if current_layout.is_some_and(|(layout, _)| layout == ClipLayout::Grid)
+                            {
+                                icons::view_grid()
+                            } else {
+                                icons::view_list()
+                            },
+                        )
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press_maybe(
+                            (!layout_items.is_empty()).then_some(Message::ToggleLayoutMenu),
+                        )
+                        .style(style::button::CustomButton::default()),
+                        view_menu(layout_items),
+                        state.layout_menu_open,
+                    )
+                    .width(MENU_WIDTH)
+                    .on_dismiss(Message::ToggleLayoutMenu);
+
                     let refresh_button = widget::button(icons::refresh())
                         .width(TOOL_BUTTON_SIZE)
                         .height(TOOL_BUTTON_SIZE)
                         .on_press(Message::RefreshClips)
                         .style(style::button::CustomButton::default());
 
-                    widget::row!(refresh_button)
+                    let mut row = widget::Row::new()
                         .spacing(SPACING_SMALL)
-                        .align_items(Alignment::Center)
+                        .align_items(Alignment::Center);
+
+                    if !state.favorites_selected
+                        && state.get_current_tab().is_some_and(Tab::is_collection)
+                    {
+                        row = row.push(
+                            widget::button(icons::document_new())
+                                .width(TOOL_BUTTON_SIZE)
+                                .height(TOOL_BUTTON_SIZE)
+                                .on_press(Message::PickCollectionFiles)
+                                .style(style::button::CustomButton::default()),
+                        );
+                    }
+
+                    row.push(layout_button)
+                        .push(sort_button)
+                        .push(metadata_button)
+                        .push(refresh_button)
                 };
 
                 widget::container(
@@ -167,6 +466,152 @@ impl SoundboardApp {
         }
     }
 
+    fn view_search_bar(&self) -> Element<'_, Message> {
+        match self {
+            Self::Loaded(state) => {
+                let input = widget::text_input("Search clips...", &state.search_query)
+                    .id(search_input_id())
+                    .on_input(Message::SearchChanged)
+                    .on_submit(Message::SearchSubmitted)
+                    .padding([SPACING_SMALL, SPACING_NORMAL])
+                    .width(Length::Fill);
+
+                let scope_button = widget::button(
+                    widget::text(if state.search_all_tabs {
+                        "All tabs"
+                    } else {
+                        "Current tab"
+                    })
+                    .horizontal_alignment(alignment::Horizontal::Center)
+                    .width(Length::Fill),
+                )
+                .width(Length::Fixed(104.0))
+                .padding([SPACING_SMALL, SPACING_NORMAL])
+                .on_press(Message::SearchScopeToggled)
+                .style(if state.search_all_tabs {
+                    style::button::CustomButton::active()
+                } else {
+                    style::button::CustomButton::default()
+                });
+
+                let mut row = widget::row!(input, scope_button)
+                    .spacing(SPACING_SMALL)
+                    .align_items(Alignment::Center);
+
+                if state.is_searching() {
+                    row = row.push(
+                        widget::button(icons::cancel())
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .on_press(Message::ClearSearch)
+                            .style(style::button::CustomButton::flat()),
+                    );
+                }
+
+                row.push(
+                    widget::button(icons::duplicates())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::ToggleDuplicates)
+                        .style(if state.duplicates_open {
+                            style::button::CustomButton::active()
+                        } else {
+                            style::button::CustomButton::default()
+                        }),
+                )
+                .push(
+                    widget::button(icons::history())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::ToggleHistory)
+                        .style(if state.history_open {
+                            style::button::CustomButton::active()
+                        } else {
+                            style::button::CustomButton::default()
+                        }),
+                )
+                .push(self.view_profile_menu())
+                .into()
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// A button showing the current profile that opens a list of all of them.
+    fn view_profile_menu(&self) -> Element<'_, Message> {
+        match self {
+            Self::Loaded(state) => {
+                let mut items: Vec<(String, Message)> = state
+                    .profiles
+                    .iter()
+                    .map(|name| {
+                        let label = if *name == state.profile {
+                            format!("• {name}")
+                        } else {
+                            name.clone()
+                        };
+                        (label, Message::SwitchProfile(name.clone()))
+                    })
+                    .collect();
+                if saving::profiles_enabled() {
+                    items.push(("New profile...".to_owned(), Message::NewProfile));
+                }
+                items.push(("Export profile...".to_owned(), Message::ExportPack(None)));
+
+                DropDown::new(
+                    widget::button(widget::text(truncate_text(&state.profile, 16)))
+                        .height(TOOL_BUTTON_SIZE)
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .on_press(Message::ToggleProfileMenu)
+                        .style(style::button::CustomButton::default()),
+                    view_menu(items),
+                    state.profile_menu_open,
+                )
+                .width(MENU_WIDTH)
+                .on_dismiss(Message::ToggleProfileMenu)
+                .into()
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Asks for the name of a new profile.
+    fn view_profile_editor(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let name = state.new_profile_name.as_ref()?;
+                let can_create = !name.trim().is_empty();
+
+                let row = widget::row!(
+                    widget::text("New profile"),
+                    widget::text_input("Name", name)
+                        .id(profile_input_id())
+                        .on_input(Message::NewProfileNameChanged)
+                        .on_submit(Message::CreateProfile)
+                        .padding([SPACING_SMALL, SPACING_NORMAL]),
+                    widget::button(widget::text("Create"))
+                        .on_press_maybe(can_create.then_some(Message::CreateProfile))
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .style(style::button::CustomButton::default()),
+                    widget::button(icons::cancel())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::CancelNewProfile)
+                        .style(style::button::CustomButton::flat()),
+                )
+                .spacing(SPACING_NORMAL)
+                .align_items(Alignment::Center);
+
+                Some(
+                    widget::container(row)
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .into(),
+                )
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
     fn view_controls(&self) -> Element<Message> {
         match self {
             Self::Loaded(state) => {
@@ -225,51 +670,119 @@ impl SoundboardApp {
     fn view_content(&self) -> Element<Message> {
         match self {
             Self::Loaded(state) => {
-                if let Some(tab) = state.get_current_tab() {
-                    let clips = tab
-                        .clips
-                        .iter()
-                        .enumerate()
-                        .fold(widget::Column::new(), |column, (idx, clip)| {
-                            column.push(
-                                widget::button(
-                                    widget::row!(
-                                        widget::text(clip.name.as_str()),
-                                        widget::horizontal_space(),
-                                        widget::text(format_seconds_to_time(
-                                            clip.duration.as_secs_f64()
-                                        )),
-                                    )
-                                    .height(Length::Fill)
-                                    .align_items(Alignment::Center),
-                                )
-                                .width(Length::Fill)
-                                .height(Length::Fixed(48.0))
-                                .padding([0, SPACING_NORMAL])
-                                .on_press(Message::StartPlayback(clip.clone()))
-                                .style(style::button::CustomButton::flat()),
-                            )
-                        })
-                        .padding([0, SPACING_NORMAL]);
+                if state.favorites_selected || state.get_current_tab().is_some() {
+                    let entries = state.displayed_clips();
+
+                    let list: Element<Message> = if entries.is_empty() {
+                        let message = if state.is_searching() || !state.tag_filter.is_empty() {
+                            "No matching clips"
+                        } else if state.favorites_selected {
+                            "Star clips to add them to your favorites"
+                        } else if state.get_current_tab().is_some_and(Tab::is_collection) {
+                            "Drop files here or add clips from other tabs"
+                        } else if state.get_current_tab().is_some_and(Tab::is_smart) {
+                            "No clips match this query"
+                        } else if state
+                            .get_current_tab()
+                            .is_some_and(|tab| !tab.variant_groups.is_empty())
+                        {
+                            "Every clip is part of a variant group"
+                        } else {
+                            "No clips found in this folder"
+                        };
 
-                    widget::container(
-                        widget::scrollable(clips)
+                        widget::container(widget::text(message))
+                            .width(Length::Fill)
                             .height(Length::Fill)
-                            .direction(Direction::Vertical(scrollable_properties()))
-                            .style(style::scrollable::CustomScrollable::default()),
-                    )
-                    .width(Length::Fill)
-                    .height(Length::Fill)
-                    .padding([SPACING_NORMAL, 0])
-                    .style(
-                        style::container::CustomContainer::default().with_border_radius([
-                            0.0,
-                            BORDER_RADIUS,
-                            BORDER_RADIUS,
-                            BORDER_RADIUS,
-                        ]),
-                    )
-                    .into()
+                            .center_x()
+                            .center_y()
+                            .into()
+                    } else {
+                        let show_folder = state.favorites_selected
+                            || state.get_current_tab().is_some_and(Tab::is_smart)
+                            || (state.is_searching() && state.search_all_tabs);
+                        let grid_columns = state
+                            .get_current_tab()
+                            .filter(|tab| {
+                                !state.favorites_selected && tab.layout == ClipLayout::Grid
+                            })
+                            .map(|tab| tab.grid_columns);
+
+                        if let Some(columns) = grid_columns {
+                            view_clip_grid(state, entries, columns)
+                        } else {
+                            let clips =
+                                entries
+                                    .into_iter()
+                                    .fold(widget::Column::new(), |column, (tab_idx, clip)| {
+                                        let button = widget::button(view_clip_row(
+                                            state,
+                                            tab_idx,
+                                            clip,
+                                            show_folder,
+                                        ))
+                                        .width(Length::Fill)
+                                        .height(Length::Fixed(48.0))
+                                        .padding([0, SPACING_NORMAL])
+                                        .on_press(Message::StartPlayback(clip.clone()))
+                                        .style(if is_drag_target(state, clip) {
+                                            style::button::CustomButton::active()
+                                        } else {
+                                            style::button::CustomButton::flat()
+                                        });
+
+                                        column.push(view_clip_entry(state, tab_idx, clip, button))
+                                    })
+                                    .padding([0, SPACING_NORMAL]);
+
+                            widget::scrollable(clips)
+                                .id(clips_scrollable_id())
+                                .on_scroll(clips_scrolled)
+                                .height(Length::Fill)
+                                .direction(Direction::Vertical(scrollable_properties()))
+                                .style(style::scrollable::CustomScrollable::default())
+                                .into()
+                        }
+                    };
+
+                    let mut column = widget::Column::new()
+                        .height(Length::Fill)
+                        .spacing(SPACING_NORMAL);
+                    if let Some(header) = self.view_query_editor() {
+                        column = column.push(header);
+                    }
+                    if let Some(chips) = self.view_tag_filter() {
+                        column = column.push(chips);
+                    }
+                    if let Some(selection) = self.view_selection_bar() {
+                        column = column.push(selection);
+                    }
+                    if let Some(editor) = self.view_label_editor() {
+                        column = column.push(editor);
+                    }
+                    if let Some(bar) = self.view_file_bar() {
+                        column = column.push(bar);
+                    }
+                    if let Some(bar) = self.view_import_bar() {
+                        column = column.push(bar);
+                    }
+                    if let Some(bar) = self.view_variant_bar() {
+                        column = column.push(bar);
+                    }
+
+                    widget::container(column.push(list))
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .padding([SPACING_NORMAL, 0])
+                        .style(
+                            style::container::CustomContainer::default().with_border_radius([
+                                0.0,
+                                BORDER_RADIUS,
+                                BORDER_RADIUS,
+                                BORDER_RADIUS,
+                            ]),
+                        )
+                        .into()
                 } else {
                     widget::container(widget::text("Please create a tab"))
                         .width(Length::Fill)
@@ -284,219 +797,2399 @@ impl SoundboardApp {
         }
     }
 
-    fn view_playbacks(&self) -> Element<Message> {
+    /// Shows the query of the selected smart tab along with any parse error.
+    fn view_query_editor(&self) -> Option<Element<'_, Message>> {
         match self {
             Self::Loaded(state) => {
-                let playbacks = state
-                    .active_playbacks
-                    .iter()
-                    .rev()
-                    .fold(widget::Column::new(), |column, (id, playback)| {
-                        let title =
-                            widget::text(truncate_text(playback.clip.name.as_str(), 16).as_str())
-                                .width(Length::Fixed(128.0));
-
-                        let play_button = widget::button(icons::play())
-                            .width(TOOL_BUTTON_SIZE)
-                            .height(TOOL_BUTTON_SIZE)
-                            .on_press(Message::AudioEvent(*id, AudioCommand::Play))
-                            .style(style::button::CustomButton::active());
-
-                        let pause_button = widget::button(icons::pause())
-                            .width(TOOL_BUTTON_SIZE)
-                            .height(TOOL_BUTTON_SIZE)
-                            .on_press(Message::AudioEvent(*id, AudioCommand::Pause))
-                            .style(style::button::CustomButton::flat());
+                if state.favorites_selected {
+                    return None;
+                }
 
-                        let control_button = if playback.handle.state() == PlaybackState::Playing {
-                            pause_button
-                        } else {
-                            play_button
-                        };
+                let tab_idx = state.current_tab;
+                let TabKind::Smart { query } = &state.get_current_tab()?.kind else {
+                    return None;
+                };
 
-                        let slider = widget::slider(
-                            0.0..=playback.clip.duration.as_secs_f64(),
-                            playback.handle.position(),
-                            |value| Message::AudioEvent(*id, AudioCommand::Seek(value)),
-                        )
-                        .step(0.001)
-                        .style(style::slider::CustomSlider::default());
+                let input = widget::text_input("e.g. duration < 5s tag:meme", query)
+                    .on_input(move |query| Message::SmartQueryChanged(tab_idx, query))
+                    .padding([SPACING_SMALL, SPACING_NORMAL]);
 
-                        let playback_position =
-                            widget::text(format_seconds_to_time(playback.handle.position()));
+                let mut column = widget::column!(input).spacing(SPACING_SMALL);
+                if let Err(error) = Query::parse(query) {
+                    column = column.push(widget::text(error.to_string()).size(FONT_SIZE_SMALL));
+                }
 
-                        let playback_duration = widget::text(format_seconds_to_time(
-                            playback.clip.duration.as_secs_f64(),
-                        ));
+                Some(
+                    widget::container(column)
+                        .padding([0, SPACING_NORMAL * 2])
+                        .into(),
+                )
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
 
-                        let stop_button = widget::button(icons::stop())
-                            .width(TOOL_BUTTON_SIZE)
-                            .height(TOOL_BUTTON_SIZE)
-                            .on_press(Message::AudioEvent(*id, AudioCommand::Stop))
-                            .style(style::button::CustomButton::flat());
+    /// Shows a chip for every user tag; selected chips narrow down the list.
+    fn view_tag_filter(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let tags = state.all_tags();
+                if tags.is_empty() {
+                    return None;
+                }
 
-                        column.push(
-                            widget::row!(
-                                title,
-                                control_button,
-                                playback_position,
-                                slider,
-                                playback_duration,
-                                stop_button
+                let chips =
+                    tags.into_iter()
+                        .fold(widget::Row::new().spacing(SPACING_SMALL), |row, tag| {
+                            row.push(
+                                widget::button(
+                                    widget::text(format!("#{tag}")).size(FONT_SIZE_SMALL),
+                                )
+                                .padding([2, SPACING_NORMAL])
+                                .on_press(Message::ToggleTagFilter(tag.to_owned()))
+                                .style(
+                                    if state.tag_filter.contains(tag) {
+                                        style::button::CustomButton::active()
+                                    } else {
+                                        style::button::CustomButton::default()
+                                    },
+                                ),
                             )
-                            .spacing(SPACING_NORMAL)
-                            .align_items(Alignment::Center),
-                        )
-                    })
-                    .spacing(SPACING_SMALL);
+                        });
 
-                widget::container(
-                    widget::scrollable(playbacks)
-                        .direction(Direction::Vertical(scrollable_properties()))
-                        .style(style::scrollable::CustomScrollable::default()),
+                Some(
+                    widget::container(
+                        widget::scrollable(chips)
+                            .direction(Direction::Horizontal(scrollable_properties()))
+                            .style(style::scrollable::CustomScrollable::default()),
+                    )
+                    .padding([0, SPACING_NORMAL * 2])
+                    .into(),
                 )
-                .width(Length::Fill)
-                .max_height(256)
-                .padding(SPACING_NORMAL)
-                .style(style::container::CustomContainer::default())
-                .into()
             }
             Self::Loading => unreachable!(),
         }
     }
-}
 
-fn create_settings_slider<'a>(
-    icon: Element<'a, Message>,
-    label_text: &str,
-    enabled: bool,
-    on_press_message: Message,
-    range: std::ops::RangeInclusive<f64>,
-    value: f64,
-    on_change_message: impl Fn(f64) -> Message + 'static,
-) -> widget::Row<'a, Message> {
-    let label_row = {
-        let button = widget::button(icon)
-            .width(TOOL_BUTTON_SIZE)
-            .height(TOOL_BUTTON_SIZE)
-            .style(if enabled {
-                style::button::CustomButton::flat()
-            } else {
-                style::button::CustomButton::active().into()
-            })
+    /// Bulk actions for the clips picked through their checkboxes.
+    /// Shows the label input for the clip picked from its context menu.
+    fn view_label_editor(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let (path, label) = state.label_edit.as_ref()?;
+
+                let input = widget::text_input(&file_name_label(path), label)
+                    .id(label_input_id())
+                    .on_input(Message::ClipLabelChanged)
+                    .on_submit(Message::SubmitClipLabel)
+                    .padding([SPACING_SMALL, SPACING_NORMAL]);
+
+                let row = widget::row!(
+                    widget::text("Label"),
+                    input,
+                    widget::button(widget::text("Save"))
+                        .on_press(Message::SubmitClipLabel)
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .style(style::button::CustomButton::default()),
+                    widget::button(icons::cancel())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::CancelClipLabel)
+                        .style(style::button::CustomButton::flat()),
+                )
+                .spacing(SPACING_NORMAL)
+                .align_items(Alignment::Center);
+
+                Some(widget::container(row).padding([0, SPACING_NORMAL]).into())
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Lists every tab, filtered by a search field, for when they no longer
+    /// fit in the tab bar.
+    fn view_tab_menu(&self) -> Element<'_, Message> {
+        match self {
+            Self::Loaded(state) => {
+                let matches = state.tab_menu_matches();
+
+                let mut search = widget::text_input("Search tabs", &state.tab_menu_query)
+                    .id(tab_menu_input_id())
+                    .on_input(Message::TabMenuQueryChanged)
+                    .padding([SPACING_SMALL, SPACING_NORMAL]);
+                if let Some(first) = matches.first() {
+                    search = search.on_submit(Message::SelectTab(*first));
+                }
+
+                let list: Element<Message> = if matches.is_empty() {
+                    widget::container(widget::text("No matching tabs"))
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .into()
+                } else {
+                    let column = matches
+                        .into_iter()
+                        .fold(widget::Column::new(), |column, idx| {
+                            let name = &state.tabs[idx].name;
+                            let label = if !state.favorites_selected && idx == state.current_tab {
+                                format!("• {name}")
+                            } else {
+                                name.clone()
+                            };
+
+                            column.push(
+                                widget::button(widget::text(label))
+                                    .width(Length::Fill)
+                                    .padding([SPACING_SMALL, SPACING_NORMAL])
+                                    .on_press(Message::SelectTab(idx))
+                                    .style(style::button::CustomButton::flat()),
+                            )
+                        });
+
+                    widget::scrollable(column)
+                        .direction(Direction::Vertical(scrollable_properties()))
+                        .style(style::scrollable::CustomScrollable::default())
+                        .into()
+                };
+
+                widget::container(widget::column!(search, list).spacing(SPACING_SMALL))
+                    .width(MENU_WIDTH)
+                    .max_height(TAB_MENU_HEIGHT)
+                    .padding(SPACING_SMALL)
+                    .style(style::container::CustomContainer::tooltip())
+                    .into()
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Shows the random pad of the current view and the tab's variant groups.
+    fn view_variant_bar(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let groups = state
+                    .get_current_tab()
+                    .filter(|_| !state.favorites_selected)
+                    .map_or(&[][..], |tab| tab.variant_groups.as_slice());
+                let has_clips = !state.displayed_clips().is_empty();
+                if !has_clips && groups.is_empty() {
+                    return None;
+                }
+
+                let random_button = widget::button(
+                    widget::row!(icons::shuffle(), widget::text("Random"))
+                        .spacing(SPACING_SMALL)
+                        .align_items(Alignment::Center),
+                )
+                .padding([SPACING_SMALL, SPACING_NORMAL])
+                .on_press_maybe(has_clips.then_some(Message::PlayRandom))
+                .style(style::button::CustomButton::default());
+
+                let row = groups.iter().enumerate().fold(
+                    widget::row!(random_button)
+                        .spacing(SPACING_SMALL)
+                        .align_items(Alignment::Center),
+                    |row, (idx, group)| {
+                        let button = widget::button(widget::text(format!(
+                            "{} ({})",
+                            group.name,
+                            group.clips.len()
+                        )))
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .on_press(Message::PlayVariant(idx))
+                        .style(style::button::CustomButton::default());
+
+                        row.push(ContextMenu::new(button, move || {
+                            view_menu(vec![("Ungroup", Message::Ungroup(idx))])
+                        }))
+                    },
+                );
+
+                Some(
+                    widget::container(
+                        widget::scrollable(row)
+                            .direction(Direction::Horizontal(scrollable_properties()))
+                            .style(style::scrollable::CustomScrollable::default()),
+                    )
+                    .padding([0, SPACING_NORMAL])
+                    .into(),
+                )
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Offers to bring back the last closed tab.
+    fn view_closed_tab_bar(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let (_, tab) = state.closed_tab.as_ref()?;
+
+                let row = widget::row!(
+                    widget::text(format!("Closed {}", tab.name)).width(Length::Fill),
+                    widget::button(widget::text("Undo"))
+                        .on_press(Message::UndoCloseTab)
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .style(style::button::CustomButton::default()),
+                    widget::button(icons::cancel())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::DismissClosedTab)
+                        .style(style::button::CustomButton::flat()),
+                )
+                .spacing(SPACING_NORMAL)
+                .align_items(Alignment::Center);
+
+                Some(
+                    widget::container(row)
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .into(),
+                )
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Asks to confirm a rename, move or trash of a clip's file and offers to
+    /// undo the last one.
+    fn view_file_bar(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let mut row = widget::Row::new()
+                    .spacing(SPACING_NORMAL)
+                    .align_items(Alignment::Center);
+
+                if let Some(edit) = &state.file_edit {
+                    let name = file_name_label(edit.path());
+                    row = match edit {
+                        FileEdit::Rename { name: new_name, .. } => {
+                            row.push(widget::text("Rename")).push(
+                                widget::text_input(&name, new_name)
+                                    .id(rename_input_id())
+                                    .on_input(Message::RenameChanged)
+                                    .on_submit(Message::ConfirmFileEdit)
+                                    .padding([SPACING_SMALL, SPACING_NORMAL]),
+                            )
+                        }
+                        FileEdit::Move { tab, .. } => row.push(
+                            widget::text(format!(
+                                "Move \"{}\" to {}?",
+                                name,
+                                state.tabs.get(*tab).map_or("", |tab| tab.name.as_str())
+                            ))
+                            .width(Length::Fill),
+                        ),
+                        FileEdit::Trash { .. } => row.push(
+                            widget::text(format!("Move \"{name}\" to the trash?"))
+                                .width(Length::Fill),
+                        ),
+                    };
+                    row = row
+                        .push(
+                            widget::button(widget::text(match edit {
+                                FileEdit::Rename { .. } => "Rename",
+                                FileEdit::Move { .. } => "Move",
+                                FileEdit::Trash { .. } => "Move to trash",
+                            }))
+                            .on_press(Message::ConfirmFileEdit)
+                            .padding([SPACING_SMALL, SPACING_NORMAL])
+                            .style(style::button::CustomButton::default()),
+                        )
+                        .push(
+                            widget::button(icons::cancel())
+                                .width(TOOL_BUTTON_SIZE)
+                                .height(TOOL_BUTTON_SIZE)
+                                .on_press(Message::CancelFileEdit)
+                                .style(style::button::CustomButton::flat()),
+                        );
+                } else if let Some(error) = &state.file_error {
+                    row = row
+                        .push(widget::text(error.as_str()).width(Length::Fill))
+                        .push(
+                            widget::button(icons::cancel())
+                                .width(TOOL_BUTTON_SIZE)
+                                .height(TOOL_BUTTON_SIZE)
+                                .on_press(Message::DismissFileOperation)
+                                .style(style::button::CustomButton::flat()),
+                        );
+                } else if let Some(operation) = &state.last_file_operation {
+                    let description = match operation {
+                        FileOperation::Moved { from, to } if from.parent() == to.parent() => {
+                            format!("Renamed to \"{}\"", file_name_label(to))
+                        }
+                        FileOperation::Moved { to, .. } => format!(
+                            "Moved \"{}\" to {}",
+                            file_name_label(to),
+                            to.parent()
+                                .map(|parent| parent.display().to_string())
+                                .unwrap_or_default()
+                        ),
+                        FileOperation::Trashed { original, .. } => {
+                            format!("Moved \"{}\" to the trash", file_name_label(original))
+                        }
+                    };
+                    row = row
+                        .push(widget::text(description).width(Length::Fill))
+                        .push(
+                            widget::button(widget::text("Undo"))
+                                .on_press(Message::UndoFileOperation)
+                                .padding([SPACING_SMALL, SPACING_NORMAL])
+                                .style(style::button::CustomButton::default()),
+                        )
+                        .push(
+                            widget::button(icons::cancel())
+                                .width(TOOL_BUTTON_SIZE)
+                                .height(TOOL_BUTTON_SIZE)
+                                .on_press(Message::DismissFileOperation)
+                                .style(style::button::CustomButton::flat()),
+                        );
+                } else {
+                    return None;
+                }
+
+                Some(widget::container(row).padding([0, SPACING_NORMAL]).into())
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Explains what dropping files will do while they are dragged over the
+    /// window, and reports the last import afterwards.
+    fn view_import_bar(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                let verb = match state.import_mode {
+                    ImportMode::Copy => "copy",
+                    ImportMode::Link => "link",
+                };
+
+                let row = if state.drop_hovered {
+                    let current = state
+                        .get_current_tab()
+                        .filter(|_| !state.favorites_selected && !state.tab_bar_hovered);
+                    let hint = match current.map(|tab| (&tab.kind, tab.name.as_str())) {
+                        Some((TabKind::Directory { .. }, name)) => {
+                            format!("Drop to {verb} clips into {name}, or drop a folder on the tabs to add it as a tab")
+                        }
+                        Some((TabKind::Collection { .. }, name)) => {
+                            format!("Drop to add clips to {name}")
+                        }
+                        _ => "Drop a folder to add it as a tab".to_owned(),
+                    };
+
+                    widget::row!(widget::text(hint).width(Length::Fill))
+                } else if let Some(summary) = &state.last_import {
+                    let tab = state
+                        .tabs
+                        .get(summary.tab)
+                        .map_or("", |tab| tab.name.as_str());
+                    let mut text = format!(
+                        "{} {} into {}",
+                        match state.import_mode {
+                            ImportMode::Copy => "Copied",
+                            ImportMode::Link => "Linked",
+                        },
+                        format_clip_count(summary.count),
+                        tab
+                    );
+                    if !summary.errors.is_empty() {
+                        text.push_str(&format!(", {} failed", summary.errors.len()));
+                    }
+
+                    let mode_button = |mode: ImportMode, label| {
+                        widget::button(widget::text(label).size(FONT_SIZE_SMALL))
+                            .on_press(Message::SetImportMode(mode))
+                            .padding([SPACING_SMALL, SPACING_NORMAL])
+                            .style(if state.import_mode == mode {
+                                style::button::CustomButton::active()
+                            } else {
+                                style::button::CustomButton::default()
+                            })
+                    };
+
+                    let mut labels = widget::column!(widget::text(text));
+                    for error in summary.errors.iter().take(3) {
+                        labels = labels.push(widget::text(error.as_str()).size(FONT_SIZE_SMALL));
+                    }
+
+                    widget::row!(
+                        labels.width(Length::Fill),
+                        mode_button(ImportMode::Copy, "Copy"),
+                        mode_button(ImportMode::Link, "Link"),
+                        widget::button(icons::cancel())
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .on_press(Message::DismissImport)
+                            .style(style::button::CustomButton::flat()),
+                    )
+                } else {
+                    return None;
+                };
+
+                Some(
+                    widget::container(row.spacing(SPACING_SMALL).align_items(Alignment::Center))
+                        .padding([0, SPACING_NORMAL])
+                        .into(),
+                )
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    fn view_selection_bar(&self) -> Option<Element<'_, Message>> {
+        match self {
+            Self::Loaded(state) => {
+                if state.selected_clips.is_empty() {
+                    return None;
+                }
+
+                let can_group = state.selected_clips.len() > 1
+                    && !state.favorites_selected
+                    && state.get_current_tab().is_some_and(|tab| !tab.is_smart());
+                let header = widget::row!(
+                    widget::text(format!("{} selected", state.selected_clips.len())),
+                    widget::horizontal_space(),
+                    widget::button("Group as variants")
+                        .padding([2, SPACING_NORMAL])
+                        .on_press_maybe(can_group.then_some(Message::GroupAsVariants))
+                        .style(style::button::CustomButton::default()),
+                    widget::button("Select all")
+                        .padding([2, SPACING_NORMAL])
+                        .on_press(Message::SelectAllClips)
+                        .style(style::button::CustomButton::default()),
+                    widget::button(icons::cancel())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::ClearSelection)
+                        .style(style::button::CustomButton::flat()),
+                )
+                .spacing(SPACING_SMALL)
+                .align_items(Alignment::Center);
+
+                let has_tag = !state.tag_input.trim().is_empty();
+                let tags = widget::row!(
+                    widget::text_input("Tag", &state.tag_input)
+                        .on_input(Message::TagInputChanged)
+                        .on_submit(Message::AddTagToSelection)
+                        .padding([SPACING_SMALL, SPACING_NORMAL]),
+                    widget::button("Add")
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .on_press_maybe(has_tag.then_some(Message::AddTagToSelection))
+                        .style(style::button::CustomButton::default()),
+                    widget::button("Remove")
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .on_press_maybe(has_tag.then_some(Message::RemoveTagFromSelection))
+                        .style(style::button::CustomButton::default()),
+                )
+                .spacing(SPACING_SMALL)
+                .align_items(Alignment::Center);
+
+                let colors = ClipColor::ALL.into_iter().fold(
+                    widget::Row::new()
+                        .spacing(SPACING_SMALL)
+                        .align_items(Alignment::Center),
+                    |row, color| {
+                        row.push(
+                            widget::button(widget::Space::new(SWATCH_SIZE, SWATCH_SIZE))
+                                .padding(0)
+                                .on_press(Message::SetSelectionColor(Some(color)))
+                                .style(style::button::CustomButton::swatch(color.color(), false)),
+                        )
+                    },
+                );
+                let colors = colors.push(
+                    widget::button(widget::text("None").size(FONT_SIZE_SMALL))
+                        .padding([2, SPACING_NORMAL])
+                        .on_press(Message::SetSelectionColor(None))
+                        .style(style::button::CustomButton::default()),
+                );
+
+                Some(
+                    widget::container(widget::column!(header, tags, colors).spacing(SPACING_SMALL))
+                        .padding([0, SPACING_NORMAL * 2])
+                        .into(),
+                )
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Lists the most recent plays and offers the usage report for export.
+    fn view_history(&self) -> Element<'_, Message> {
+        match self {
+            Self::Loaded(state) => {
+                let header = widget::row!(
+                    widget::text("Recently played").font(self.bold_font()),
+                    widget::horizontal_space(),
+                    widget::button(widget::text("Export CSV").size(FONT_SIZE_SMALL))
+                        .on_press(Message::ExportStats(ReportFormat::Csv))
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .style(style::button::CustomButton::default()),
+                    widget::button(widget::text("Export JSON").size(FONT_SIZE_SMALL))
+                        .on_press(Message::ExportStats(ReportFormat::Json))
+                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                        .style(style::button::CustomButton::default()),
+                    widget::button(icons::cancel())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::ToggleHistory)
+                        .style(style::button::CustomButton::flat()),
+                )
+                .spacing(SPACING_SMALL)
+                .align_items(Alignment::Center);
+
+                let plays = state.recent_plays(RECENT_PLAYS_LIMIT);
+                let list: Element<Message> = if plays.is_empty() {
+                    widget::text("Nothing played yet")
+                        .size(FONT_SIZE_SMALL)
+                        .into()
+                } else {
+                    let rows =
+                        plays
+                            .into_iter()
+                            .fold(widget::Column::new(), |column, (record, clip)| {
+                                let name = clip.map_or_else(
+                                    || file_name_label(&record.path),
+                                    |clip| state.clip_label(clip, false).to_owned(),
+                                );
+                                let row = widget::row!(
+                                    widget::text(name),
+                                    widget::horizontal_space(),
+                                    widget::text(format_seconds_to_time(record.played))
+                                        .size(FONT_SIZE_SMALL),
+                                    widget::text(history::format_elapsed(record.started))
+                                        .size(FONT_SIZE_SMALL)
+                                        .width(Length::Fixed(72.0))
+                                        .horizontal_alignment(alignment::Horizontal::Right),
+                                )
+                                .spacing(SPACING_NORMAL)
+                                .align_items(Alignment::Center);
+
+                                column.push(
+                                    widget::button(row)
+                                        .width(Length::Fill)
+                                        .padding([SPACING_SMALL, SPACING_NORMAL])
+                                        .on_press_maybe(
+                                            clip.map(|clip| Message::StartPlayback(clip.clone())),
+                                        )
+                                        .style(style::button::CustomButton::flat()),
+                                )
+                            });
+
+                    widget::scrollable(rows)
+                        .direction(Direction::Vertical(scrollable_properties()))
+                        .style(style::scrollable::CustomScrollable::default())
+                        .into()
+                };
+
+                widget::container(widget::column!(header, list).spacing(SPACING_SMALL))
+                    .width(Length::Fill)
+                    .max_height(240)
+                    .padding(SPACING_NORMAL)
+                    .style(style::container::CustomContainer::default())
+                    .into()
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    /// Shows the result of the duplicate scan with options to hide or delete copies.
+    fn view_duplicates(&self) -> Element<'_, Message> {
+        match self {
+            Self::Loaded(state) => {
+                let scan_button = widget::button(
+                    widget::text(if state.scanning_duplicates {
+                        "Scanning..."
+                    } else {
+                        "Scan"
+                    })
+                    .size(FONT_SIZE_SMALL),
+                )
+                .on_press_maybe((!state.scanning_duplicates).then_some(Message::ScanDuplicates))
+                .padding([SPACING_SMALL, SPACING_NORMAL])
+                .style(style::button::CustomButton::default());
+
+                let header = widget::row!(
+                    widget::text("Duplicates").font(self.bold_font()),
+                    widget::horizontal_space(),
+                    widget::checkbox("Compare audio", state.compare_audio)
+                        .text_size(FONT_SIZE_SMALL)
+                        .on_toggle(Message::CompareAudioToggled),
+                    scan_button,
+                    widget::button(icons::cancel())
+                        .width(TOOL_BUTTON_SIZE)
+                        .height(TOOL_BUTTON_SIZE)
+                        .on_press(Message::ToggleDuplicates)
+                        .style(style::button::CustomButton::flat()),
+                )
+                .spacing(SPACING_SMALL)
+                .align_items(Alignment::Center);
+
+                let body: Element<Message> = match &state.duplicate_groups {
+                    None => widget::text(
+                        "Scan all tabs for files with the same contents, or with \
+                         \"Compare audio\" for the same recording in another format",
+                    )
+                    .size(FONT_SIZE_SMALL)
+                    .into(),
+                    Some(groups) if groups.is_empty() => widget::text("No duplicates found")
+                        .size(FONT_SIZE_SMALL)
+                        .into(),
+                    Some(groups) => {
+                        let groups = groups.iter().enumerate().fold(
+                            widget::Column::new().spacing(SPACING_NORMAL),
+                            |column, (idx, group)| {
+                                let group_header = widget::row!(
+                                    widget::text(format!("{} copies", group.len()))
+                                        .size(FONT_SIZE_SMALL),
+                                    widget::horizontal_space(),
+                                    widget::button(
+                                        widget::text("Hide extra copies").size(FONT_SIZE_SMALL)
+                                    )
+                                    .on_press(Message::HideExtraCopies(idx))
+                                    .padding([SPACING_SMALL, SPACING_NORMAL])
+                                    .style(style::button::CustomButton::flat()),
+                                )
+                                .align_items(Alignment::Center);
+
+                                let files = group.iter().fold(
+                                    widget::Column::new().push(group_header),
+                                    |files, path| files.push(view_duplicate_file(state, path)),
+                                );
+
+                                column.push(files)
+                            },
+                        );
+
+                        widget::scrollable(groups)
+                            .direction(Direction::Vertical(scrollable_properties()))
+                            .style(style::scrollable::CustomScrollable::default())
+                            .into()
+                    }
+                };
+
+                widget::container(widget::column!(header, body).spacing(SPACING_SMALL))
+                    .width(Length::Fill)
+                    .max_height(320)
+                    .padding(SPACING_NORMAL)
+                    .style(style::container::CustomContainer::default())
+                    .into()
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+
+    fn view_playbacks(&self) -> Element<Message> {
+        match self {
+            Self::Loaded(state) => {
+                let playbacks = state
+                    .active_playbacks
+                    .iter()
+                    .rev()
+                    .fold(widget::Column::new(), |column, (id, playback)| {
+                        let title =
+                            widget::text(truncate_text(playback.clip.name.as_str(), 16).as_str())
+                                .width(Length::Fixed(128.0));
+
+                        let play_button = widget::button(icons::play())
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .on_press(Message::AudioEvent(*id, AudioCommand::Play))
+                            .style(style::button::CustomButton::active());
+
+                        let pause_button = widget::button(icons::pause())
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .on_press(Message::AudioEvent(*id, AudioCommand::Pause))
+                            .style(style::button::CustomButton::flat());
+
+                        let control_button = if playback.handle.state() == PlaybackState::Playing {
+                            pause_button
+                        } else {
+                            play_button
+                        };
+
+                        let slider = widget::slider(
+                            0.0..=playback.clip.duration.as_secs_f64(),
+                            playback.handle.position(),
+                            |value| Message::AudioEvent(*id, AudioCommand::Seek(value)),
+                        )
+                        .step(0.001)
+                        .style(style::slider::CustomSlider::default());
+
+                        let playback_position =
+                            widget::text(format_seconds_to_time(playback.handle.position()));
+
+                        let playback_duration = widget::text(format_seconds_to_time(
+                            playback.clip.duration.as_secs_f64(),
+                        ));
+
+                        let stop_button = widget::button(icons::stop())
+                            .width(TOOL_BUTTON_SIZE)
+                            .height(TOOL_BUTTON_SIZE)
+                            .on_press(Message::AudioEvent(*id, AudioCommand::Stop))
+                            .style(style::button::CustomButton::flat());
+
+                        column.push(
+                            widget::row!(
+                                title,
+                                control_button,
+                                playback_position,
+                                slider,
+                                playback_duration,
+                                stop_button
+                            )
+                            .spacing(SPACING_NORMAL)
+                            .align_items(Alignment::Center),
+                        )
+                    })
+                    .spacing(SPACING_SMALL);
+
+                widget::container(
+                    widget::scrollable(playbacks)
+                        .direction(Direction::Vertical(scrollable_properties()))
+                        .style(style::scrollable::CustomScrollable::default()),
+                )
+                .width(Length::Fill)
+                .max_height(256)
+                .padding(SPACING_NORMAL)
+                .style(style::container::CustomContainer::default())
+                .into()
+            }
+            Self::Loading => unreachable!(),
+        }
+    }
+}
+
+fn view_clip_row<'a>(
+    state: &'a AppState,
+    tab_idx: usize,
+    clip: &'a AudioClip,
+    show_folder: bool,
+) -> Element<'a, Message> {
+    let tab = &state.tabs[tab_idx];
+    let show_metadata = tab.show_metadata;
+
+    let duration = widget::text(format_seconds_to_time(clip.duration.as_secs_f64()));
+    let name = widget::text(state.clip_label(clip, show_metadata));
+
+    let star_button = widget::button(if state.is_favorite(clip) {
+        icons::starred()
+    } else {
+        icons::non_starred()
+    })
+    .width(TOOL_BUTTON_SIZE)
+    .height(TOOL_BUTTON_SIZE)
+    .on_press(Message::ToggleFavorite(clip.path.clone()))
+    .style(style::button::CustomButton::flat());
+
+    let settings = state.get_clip_settings(&clip.path);
+
+    let mut trailing = widget::Row::new()
+        .spacing(SPACING_NORMAL)
+        .align_items(Alignment::Center);
+    if let Some(settings) = settings.filter(|settings| !settings.tags.is_empty()) {
+        let tags = settings
+            .tags
+            .iter()
+            .map(|tag| format!("#{tag}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        trailing = trailing.push(widget::text(tags).size(FONT_SIZE_SMALL));
+    }
+    if show_folder {
+        trailing = trailing.push(widget::text(tab.name.as_str()).size(FONT_SIZE_SMALL));
+    }
+    let play_count = state.play_count(&clip.path);
+    if play_count > 0 {
+        trailing = trailing.push(widget::text(format_play_count(play_count)).size(FONT_SIZE_SMALL));
+    }
+    let trailing = trailing.push(duration);
+
+    let mut row = widget::Row::new()
+        .height(Length::Fill)
+        .spacing(SPACING_NORMAL)
+        .align_items(Alignment::Center);
+
+    if let Some(color) = settings.and_then(|settings| settings.color) {
+        row = row.push(
+            widget::container(widget::Space::new(4, 28))
+                .style(style::container::CustomContainer::swatch(color.color())),
+        );
+    }
+    if !state.selected_clips.is_empty() {
+        let path = clip.path.clone();
+        row = row.push(
+            widget::checkbox("", state.selected_clips.contains(&clip.path))
+                .spacing(0)
+                .on_toggle(move |_| Message::ToggleClipSelected(path.clone())),
+        );
+    }
+    if state.can_reorder() {
+        row = row.push(
+            widget::mouse_area(icons::drag_handle())
+                .on_press(Message::DragStart(clip.path.clone())),
+        );
+    }
+    let mut row = row.push(star_button);
+
+    if !show_metadata {
+        return row
+            .push(name)
+            .push(widget::horizontal_space())
+            .push(trailing)
+            .into();
+    }
+
+    if let Some(cover) = &clip.cover {
+        row = row.push(
+            widget::svg(cover.clone())
+                .width(COVER_SIZE)
+                .height(COVER_SIZE),
+        );
+    }
+
+    let mut labels = widget::column!(name);
+    if let Some(subtitle) = clip.subtitle() {
+        labels = labels.push(widget::text(subtitle).size(FONT_SIZE_SMALL));
+    }
+
+    let labels: Element<Message> = if let Some(comment) = &clip.comment {
+        widget::tooltip(labels, comment.as_str(), widget::tooltip::Position::Bottom)
+            .padding(SPACING_SMALL)
+            .style(style::container::CustomContainer::tooltip())
+            .into()
+    } else {
+        labels.into()
+    };
+
+    row.push(labels)
+        .push(widget::horizontal_space())
+        .push(trailing)
+        .into()
+}
+
+/// Lays out clips as square pads that share the width of the window evenly.
+fn view_clip_grid<'a>(
+    state: &'a AppState,
+    entries: Vec<(usize, &'a AudioClip)>,
+    columns: u16,
+) -> Element<'a, Message> {
+    let columns = usize::from(columns.max(1));
+    let spacing = f32::from(SPACING_NORMAL);
+    // window padding, grid padding and the gaps between pads
+    let available = state.window_size.width - spacing * (columns as f32 + 3.0) - SCROLLBAR_SPACE;
+    let pad_size = (available / columns as f32).floor().max(PAD_SIZE_MIN);
+
+    let grid = entries
+        .chunks(columns)
+        .fold(widget::Column::new(), |grid, chunk| {
+            let row = chunk
+                .iter()
+                .fold(widget::Row::new(), |row, &(tab_idx, clip)| {
+                    row.push(view_clip_pad(state, tab_idx, clip, pad_size))
+                })
+                .spacing(SPACING_NORMAL);
+
+            grid.push(row)
+        })
+        .spacing(SPACING_NORMAL)
+        .padding([0, SPACING_NORMAL]);
+
+    widget::scrollable(grid)
+        .id(clips_scrollable_id())
+        .on_scroll(clips_scrolled)
+        .height(Length::Fill)
+        .direction(Direction::Vertical(scrollable_properties()))
+        .style(style::scrollable::CustomScrollable::default())
+        .into()
+}
+
+fn view_clip_pad<'a>(
+    state: &'a AppState,
+    tab_idx: usize,
+    clip: &'a AudioClip,
+    size: f32,
+) -> Element<'a, Message> {
+    let show_metadata = state.tabs[tab_idx].show_metadata;
+    let color = state
+        .get_clip_settings(&clip.path)
+        .and_then(|settings| settings.color);
+
+    let mut top = widget::Row::new()
+        .spacing(SPACING_SMALL)
+        .align_items(Alignment::Center);
+    if state.can_reorder() {
+        top = top.push(
+            widget::mouse_area(icons::drag_handle())
+                .on_press(Message::DragStart(clip.path.clone())),
+        );
+    }
+    if !state.selected_clips.is_empty() {
+        let path = clip.path.clone();
+        top = top.push(
+            widget::checkbox("", state.selected_clips.contains(&clip.path))
+                .spacing(0)
+                .on_toggle(move |_| Message::ToggleClipSelected(path.clone())),
+        );
+    }
+    top = top.push(widget::horizontal_space());
+    if let Some(progress) = state.playback_progress(&clip.path) {
+        top = top.push(
+            widget::canvas(ProgressRing { progress })
+                .width(PROGRESS_RING_SIZE)
+                .height(PROGRESS_RING_SIZE),
+        );
+    }
+    top = top.push(
+        widget::button(if state.is_favorite(clip) {
+            icons::starred()
+        } else {
+            icons::non_starred()
+        })
+        .width(TOOL_BUTTON_SIZE_SMALL)
+        .height(TOOL_BUTTON_SIZE_SMALL)
+        .on_press(Message::ToggleFavorite(clip.path.clone()))
+        .style(style::button::CustomButton::flat()),
+    );
+
+    let mut content = widget::Column::new()
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .spacing(SPACING_SMALL)
+        .align_items(Alignment::Center)
+        .push(top)
+        .push(widget::vertical_space());
+    if show_metadata {
+        if let Some(cover) = &clip.cover {
+            content = content.push(
+                widget::svg(cover.clone())
+                    .width(COVER_SIZE)
+                    .height(COVER_SIZE),
+            );
+        }
+    }
+    let content = content
+        .push(
+            widget::text(truncate_text(state.clip_label(clip, show_metadata), 40))
+                .width(Length::Fill)
+                .horizontal_alignment(alignment::Horizontal::Center),
+        )
+        .push(widget::vertical_space())
+        .push(
+            widget::text(match state.play_count(&clip.path) {
+                0 => format_seconds_to_time(clip.duration.as_secs_f64()),
+                count => format!(
+                    "{} · {}",
+                    format_seconds_to_time(clip.duration.as_secs_f64()),
+                    format_play_count(count)
+                ),
+            })
+            .size(FONT_SIZE_SMALL),
+        );
+
+    let button = widget::button(content)
+        .width(size)
+        .height(size)
+        .padding(SPACING_SMALL)
+        .on_press(Message::StartPlayback(clip.clone()))
+        .style(style::button::CustomButton::pad(
+            color.map(ClipColor::color),
+            is_drag_target(state, clip),
+        ));
+
+    view_clip_entry(state, tab_idx, clip, button)
+}
+
+/// Attaches the context menu to a clip's row or pad and tracks it as a drop
+/// target while another clip is being dragged.
+fn view_clip_entry<'a>(
+    state: &'a AppState,
+    tab_idx: usize,
+    clip: &'a AudioClip,
+    button: widget::Button<'a, Message>,
+) -> Element<'a, Message> {
+    let items = clip_menu_items(state, tab_idx, clip);
+    let entry = ContextMenu::new(button, move || view_menu(items.clone()));
+
+    if state.dragging.is_some() {
+        widget::mouse_area(entry)
+            .on_enter(Message::DragOver(clip.path.clone()))
+            .into()
+    } else {
+        entry.into()
+    }
+}
+
+fn is_drag_target(state: &AppState, clip: &AudioClip) -> bool {
+    state.dragging.is_some() && state.drag_target.as_ref() == Some(&clip.path)
+}
+
+/// A circle that fills up clockwise as a clip plays.
+struct ProgressRing {
+    progress: f32,
+}
+
+impl<Message> widget::canvas::Program<Message> for ProgressRing {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<widget::canvas::Geometry> {
+        let mut frame = widget::canvas::Frame::new(renderer, bounds.size());
+        let center = frame.center();
+        let radius = bounds.width.min(bounds.height) / 2.0 - PROGRESS_RING_WIDTH;
+
+        let mut track_color = theme.palette().text;
+        track_color.a = 0.2;
+        frame.stroke(
+            &widget::canvas::Path::circle(center, radius),
+            widget::canvas::Stroke::default()
+                .with_width(PROGRESS_RING_WIDTH)
+                .with_color(track_color),
+        );
+
+        let start = -std::f32::consts::FRAC_PI_2;
+        let arc = widget::canvas::Path::new(|builder| {
+            builder.arc(widget::canvas::path::Arc {
+                center,
+                radius,
+                start_angle: iced::Radians(start),
+                end_angle: iced::Radians(start + std::f32::consts::TAU * self.progress),
+            });
+        });
+        frame.stroke(
+            &arc,
+            widget::canvas::Stroke::default()
+                .with_width(PROGRESS_RING_WIDTH)
+                .with_color(theme.palette().primary)
+                .with_line_cap(widget::canvas::LineCap::Round),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn view_duplicate_file<'a>(state: &'a AppState, path: &'a std::path::Path) -> Element<'a, Message> {
+    let hidden = state.hidden_clips.contains(path);
+    let folder = path
+        .parent()
+        .map(|parent| parent.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let labels = widget::column!(
+        widget::text(file_name_label(path)),
+        widget::text(folder).size(FONT_SIZE_SMALL)
+    );
+
+    let hide_button =
+        widget::button(widget::text(if hidden { "Unhide" } else { "Hide" }).size(FONT_SIZE_SMALL))
+            .on_press(if hidden {
+                Message::UnhideClip(path.to_path_buf())
+            } else {
+                Message::HideClip(path.to_path_buf())
+            })
+            .padding([SPACING_SMALL, SPACING_NORMAL])
+            .style(style::button::CustomButton::default());
+
+    let mut row = widget::row!(labels, widget::horizontal_space(), hide_button)
+        .spacing(SPACING_SMALL)
+        .align_items(Alignment::Center);
+
+    row = if state.pending_delete.as_deref() == Some(path) {
+        row.push(
+            widget::button(widget::text("Delete file").size(FONT_SIZE_SMALL))
+                .on_press(Message::ConfirmDelete)
+                .padding([SPACING_SMALL, SPACING_NORMAL])
+                .style(style::button::CustomButton::active()),
+        )
+        .push(
+            widget::button(icons::cancel())
+                .width(TOOL_BUTTON_SIZE)
+                .height(TOOL_BUTTON_SIZE)
+                .on_press(Message::CancelDelete)
+                .style(style::button::CustomButton::flat()),
+        )
+    } else {
+        row.push(
+            widget::button(widget::text("Delete").size(FONT_SIZE_SMALL))
+                .on_press(Message::RequestDelete(path.to_path_buf()))
+                .padding([SPACING_SMALL, SPACING_NORMAL])
+                .style(style::button::CustomButton::default()),
+        )
+    };
+
+    widget::container(row)
+        .padding([SPACING_SMALL, SPACING_NORMAL])
+        .into()
+}
+
+/// Collects the actions offered when right clicking a clip of the given tab.
+fn clip_menu_items(state: &AppState, tab_idx: usize, clip: &AudioClip) -> Vec<(String, Message)> {
+    let mut items = vec![(
+        if state.selected_clips.contains(&clip.path) {
+            "Deselect"
+        } else {
+            "Select"
+        }
+        .to_owned(),
+        Message::ToggleClipSelected(clip.path.clone()),
+    )];
+
+    items.push((
+        "Set label...".to_owned(),
+        Message::EditClipLabel(clip.path.clone()),
+    ));
+    items.push((
+        "Rename file...".to_owned(),
+        Message::StartRename(clip.path.clone()),
+    ));
+
+    let directories = state.tabs.iter().enumerate().filter(|(_, tab)| {
+        tab.directory()
+            .is_some_and(|directory| clip.path.parent() != Some(directory))
+    });
+    for (idx, tab) in directories {
+        items.push((
+            format!("Move to {}", tab.name),
+            Message::RequestMove(clip.path.clone(), idx),
+        ));
+    }
+
+    let collections = state
+        .tabs
+        .iter()
+        .enumerate()
+        .filter(|(idx, tab)| *idx != tab_idx && tab.is_collection());
+    for (idx, collection) in collections {
+        items.push((
+            format!("Add to {}", collection.name),
+            Message::AddToCollection(idx, vec![clip.path.clone()]),
+        ));
+    }
+
+    let tab = &state.tabs[tab_idx];
+    if tab.is_collection() && tab.sort == SortMode::Manual {
+        items.push((
+            "Move up".to_owned(),
+            Message::MoveInCollection(tab_idx, clip.path.clone(), -1),
+        ));
+        items.push((
+            "Move down".to_owned(),
+            Message::MoveInCollection(tab_idx, clip.path.clone(), 1),
+        ));
+    }
+    if tab.is_collection() {
+        items.push((
+            "Remove from collection".to_owned(),
+            Message::RemoveFromCollection(tab_idx, clip.path.clone()),
+        ));
+    }
+
+    items.push((
+        "Move to trash".to_owned(),
+        Message::RequestTrash(clip.path.clone()),
+    ));
+
+    items
+}
+
+fn view_menu<'a>(items: Vec<(impl ToString, Message)>) -> Element<'a, Message> {
+    let column = items
+        .into_iter()
+        .fold(widget::Column::new(), |column, (label, message)| {
+            column.push(
+                widget::button(widget::text(label.to_string()))
+                    .width(Length::Fill)
+                    .padding([SPACING_SMALL, SPACING_NORMAL])
+                    .on_press(message)
+                    .style(style::button::CustomButton::flat()),
+            )
+        });
+
+    widget::container(column)
+        .width(MENU_WIDTH)
+        .padding(SPACING_SMALL)
+        .style(style::container::CustomContainer::tooltip())
+        .into()
+}
+
+fn create_settings_slider<'a>(
+    icon: Element<'a, Message>,
+    label_text: &str,
+    enabled: bool,
+    on_press_message: Message,
+    range: std::ops::RangeInclusive<f64>,
+    value: f64,
+    on_change_message: impl Fn(f64) -> Message + 'static,
+) -> widget::Row<'a, Message> {
+    let label_row = {
+        let button = widget::button(icon)
+            .width(TOOL_BUTTON_SIZE)
+            .height(TOOL_BUTTON_SIZE)
+            .style(if enabled {
+                style::button::CustomButton::flat()
+            } else {
+                style::button::CustomButton::active().into()
+            })
             .on_press(on_press_message);
 
-        let label = widget::text(label_text).width(Length::Fixed(56.0));
+        let label = widget::text(label_text).width(Length::Fixed(56.0));
+
+        widget::row!(button, label)
+            .width(Length::Shrink)
+            .spacing(SPACING_SMALL)
+            .align_items(Alignment::Center)
+    };
+
+    let slider = widget::slider(range, value, on_change_message)
+        // .width(Length::Fixed(256.0))
+        .step(0.01)
+        .on_release(Message::SetDirty)
+        .style(style::slider::CustomSlider::default());
+
+    let value_text = widget::text(format!("{:.0}%", value * 100.0))
+        .horizontal_alignment(alignment::Horizontal::Left);
+
+    widget::row!(label_row, slider, value_text)
+        .width(Length::Fixed(384.0))
+        .spacing(SPACING_NORMAL)
+        .align_items(Alignment::Center)
+}
+
+fn format_seconds_to_time(seconds: f64) -> String {
+    let total_seconds = seconds as u64;
+    let minutes = total_seconds / 60;
+    let seconds = total_seconds % 60;
+
+    format!("{:02}:{:02}", minutes, seconds)
+}
+
+fn format_clip_count(count: usize) -> String {
+    if count == 1 {
+        "1 clip".to_owned()
+    } else {
+        format!("{count} clips")
+    }
+}
+
+fn format_play_count(count: u32) -> String {
+    if count == 1 {
+        "1 play".to_owned()
+    } else {
+        format!("{count} plays")
+    }
+}
+
+fn truncate_text(text: &str, max_length: usize) -> String {
+    if text.len() > max_length && max_length > 3 {
+        format!("{}...", &text[..max_length - 3])
+    } else {
+        text.to_string()
+    }
+}
+
+fn rename_input_id() -> widget::text_input::Id {
+    widget::text_input::Id::new("rename")
+}
+
+fn label_input_id() -> widget::text_input::Id {
+    widget::text_input::Id::new("label")
+}
+
+fn profile_input_id() -> widget::text_input::Id {
+    widget::text_input::Id::new("profile")
+}
+
+pub fn clips_scrollable_id() -> widget::scrollable::Id {
+    widget::scrollable::Id::new("clips")
+}
+
+fn tab_menu_input_id() -> widget::text_input::Id {
+    widget::text_input::Id::new("tab-menu")
+}
+
+fn tab_rename_input_id() -> widget::text_input::Id {
+    widget::text_input::Id::new("tab-rename")
+}
+
+fn file_name_label(path: &std::path::Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn search_input_id() -> widget::text_input::Id {
+    widget::text_input::Id::new("search")
+}
+
+fn scrollable_properties() -> Properties {
+    Properties::default().scroller_width(4.0).width(4)
+}
+
+async fn get_audio_files_async() -> Vec<std::path::PathBuf> {
+    let files = rfd::AsyncFileDialog::new()
+        .add_filter("Audio", &["mp3", "wav", "flac", "ogg"])
+        .pick_files()
+        .await;
+
+    files
+        .unwrap_or_default()
+        .iter()
+        .map(|handle| handle.path().to_path_buf())
+        .collect()
+}
+
+async fn export_stats_async(contents: String, format: ReportFormat) -> Option<std::path::PathBuf> {
+    let extension = format.extension();
+    let file = rfd::AsyncFileDialog::new()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("soundboard-stats.{extension}"))
+        .save_file()
+        .await?;
+
+    let path = file.path().to_path_buf();
+    match async_std::fs::write(&path, contents).await {
+        Ok(()) => Some(path),
+        Err(error) => {
+            println!("Could not export stats to {}: {}", path.display(), error);
+            None
+        }
+    }
+}
+
+async fn export_pack_async(
+    tabs: Vec<Tab>,
+    clip_settings: std::collections::BTreeMap<std::path::PathBuf, ClipSettings>,
+    name: String,
+) -> Option<Result<std::path::PathBuf, pack::PackError>> {
+    let file = rfd::AsyncFileDialog::new()
+        .add_filter("Board pack", &[pack::EXTENSION])
+        .set_file_name(format!("{name}.{}", pack::EXTENSION))
+        .save_file()
+        .await?;
+
+    let path = file.path().to_path_buf();
+    Some(
+        pack::export(tabs, clip_settings, path.clone())
+            .await
+            .map(|_| path),
+    )
+}
+
+/// Asks for a board pack and the folder to unpack it into, then unpacks it.
+async fn import_pack_async() -> Option<Result<pack::Board, pack::PackError>> {
+    let file = rfd::AsyncFileDialog::new()
+        .add_filter("Board pack", &[pack::EXTENSION])
+        .pick_file()
+        .await?;
+    let folder = rfd::AsyncFileDialog::new()
+        .set_title("Unpack into")
+        .pick_folder()
+        .await?;
+
+    Some(pack::import(file.path().to_path_buf(), folder.path().to_path_buf()).await)
+}
+
+/// Asks for another app's config or playlist, or for a folder of category
+/// folders, and reads it.
+async fn import_board_async(folder: bool) -> Option<Result<pack::Board, importers::ImportError>> {
+    let dialog = rfd::AsyncFileDialog::new();
+    let picked = if folder {
+        dialog.pick_folder().await?
+    } else {
+        dialog
+            .add_filter("Boards and playlists", &importers::EXTENSIONS)
+            .pick_file()
+            .await?
+    };
+
+    Some(importers::import(picked.path().to_path_buf()).await)
+}
+
+async fn get_backup_async() -> Option<std::path::PathBuf> {
+    let file = rfd::AsyncFileDialog::new()
+        .add_filter("Config", &["json"])
+        .set_directory(saving::backup_dir())
+        .pick_file()
+        .await?;
+
+    Some(file.path().to_path_buf())
+}
+
+async fn get_dir_async() -> Option<std::path::PathBuf> {
+    let folder = rfd::AsyncFileDialog::new().pick_folder().await;
+    let path = folder.map(|handle| handle.path().to_path_buf());
+
+    if let Some(path) = path {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn clips_scrolled(viewport: widget::scrollable::Viewport) -> Message {
+    Message::ClipsScrolled(viewport.relative_offset().y)
+}
+
+/// Scrolls the clip list back to where it was left for the clips shown.
+fn restore_scroll(state: &AppState) -> Command<Message> {
+    widget::scrollable::snap_to(
+        clips_scrollable_id(),
+        widget::scrollable::RelativeOffset {
+            x: 0.0,
+            y: state.scroll(),
+        },
+    )
+}
+
+/// Switches to a tab, loading its clips the first time it is shown.
+fn open_tab(state: &mut AppState, index: usize) -> Command<Message> {
+    if index >= state.tabs.len() {
+        return Command::none();
+    }
+
+    println!("Tab selected: {}", index);
+
+    state.select_tab(index);
+    state.set_dirty();
+
+    if state.get_current_tab().unwrap().is_smart() {
+        state.load_missing_clips(); // TODO: move to async
+    } else if state.get_current_tab().unwrap().clips.is_empty() {
+        println!("Tab is empty, refreshing clips...");
+        state.refresh_clips(); // TODO: move to async
+    }
+
+    restore_scroll(state)
+}
+
+pub fn update(state: &mut AppState, message: &Message) -> Command<Message> {
+    let command = match message {
+        // a save of the profile that was switched away from
+        Message::Saved(profile, _) if *profile != state.profile => Command::none(),
+        Message::Saved(_, result) => {
+            match result {
+                Ok(()) => {
+                    println!("Saved!");
+                    state.save();
+                }
+                Err(error) => {
+                    state.save_failed(error);
+                    if let Some(profile) = state.switching_to.take() {
+                        let text = format!(
+                            "Did not switch to {profile}, the changes to this profile could not be saved"
+                        );
+                        state.notify(text, None);
+                    }
+                }
+            }
+
+            // a save was underway when the window was asked to close
+            if state.closing {
+                return close_command(state);
+            }
+
+            continue_switch(state)
+        }
+        Message::CloseRequested => {
+            state.closing = true;
+            state.last_file_operation = None;
+            state.release_trash();
+
+            if state.saving {
+                Command::none()
+            } else {
+                close_command(state)
+            }
+        }
+        Message::Close => window::close(window::Id::MAIN),
+        Message::CheckSettings => {
+            state.load_settings();
+
+            Command::none()
+        }
+        Message::WindowMoved(position) => {
+            state.window_position = Some(*position);
+
+            Command::none()
+        }
+        Message::ClipsScrolled(scroll) => {
+            state.set_scroll(*scroll);
+
+            Command::none()
+        }
+        Message::SelectTab(index) => {
+            state.tab_menu_open = false;
+            state.tab_menu_query.clear();
+
+            open_tab(state, *index)
+        }
+        Message::CycleTab(offset) => {
+            if !state.tabs.is_empty() {
+                let index = if state.favorites_selected {
+                    // the favorites tab sits before the first tab
+                    if *offset > 0 {
+                        0
+                    } else {
+                        state.tabs.len() - 1
+                    }
+                } else {
+                    (state.current_tab as isize + offset).rem_euclid(state.tabs.len() as isize)
+                        as usize
+                };
+                open_tab(state, index)
+            } else {
+                Command::none()
+            }
+        }
+        Message::ToggleTabMenu => {
+            state.tab_menu_open = !state.tab_menu_open;
+            state.tab_menu_query.clear();
+
+            if state.tab_menu_open {
+                widget::text_input::focus(tab_menu_input_id())
+            } else {
+                Command::none()
+            }
+        }
+        Message::TabMenuQueryChanged(query) => {
+            state.tab_menu_query = query.clone();
+
+            Command::none()
+        }
+        Message::CloseTab(index) => {
+            println!("Tab closed: {}", index);
+
+            state.close_tab(*index);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::UndoCloseTab => {
+            state.undo_close_tab();
+            state.set_dirty();
+            if state
+                .get_current_tab()
+                .is_some_and(|tab| tab.clips.is_empty())
+            {
+                state.refresh_clips(); // TODO: move to async
+            }
+
+            Command::none()
+        }
+        Message::Undo => {
+            if state.undo() {
+                println!("Edit undone.");
+                state.set_dirty();
+            }
+
+            restore_scroll(state)
+        }
+        Message::Redo => {
+            if state.redo() {
+                println!("Edit redone.");
+                state.set_dirty();
+            }
+
+            restore_scroll(state)
+        }
+        Message::GroupAsVariants => {
+            if state.group_selection_as_variants() {
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::Ungroup(group) => {
+            state.ungroup_variants(*group);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::DismissClosedTab => {
+            state.closed_tab = None;
+
+            Command::none()
+        }
+        Message::StartTabRename(index) => {
+            state.tab_rename = state.tabs.get(*index).map(|tab| (*index, tab.name.clone()));
+
+            widget::text_input::focus(tab_rename_input_id())
+        }
+        Message::TabRenameChanged(name) => {
+            if let Some((_, text)) = &mut state.tab_rename {
+                *text = name.clone();
+            }
+
+            Command::none()
+        }
+        Message::SubmitTabRename => {
+            if state.submit_tab_rename() {
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::DuplicateTab(index) => {
+            state.duplicate_tab(*index);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::ChangeTabDirectory(index) => {
+            let index = *index;
+            Command::perform(get_dir_async(), move |path| {
+                Message::TabDirectoryChosen(index, path)
+            })
+        }
+        Message::TabDirectoryChosen(index, path) => {
+            if let (Some(tab), Some(path)) = (state.tabs.get_mut(*index), path) {
+                println!("Tab {} now points at {:?}", index, path);
+
+                tab.set_directory(path.clone());
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::TabDragStart(index) => {
+            state.tab_dragging = Some(*index);
+            state.tab_drag_target = Some(*index);
+
+            open_tab(state, *index)
+        }
+        Message::TabDragOver(index) => {
+            if state.tab_dragging.is_some() {
+                state.tab_drag_target = Some(*index);
+            }
+
+            Command::none()
+        }
+        Message::ToggleNewTabMenu => {
+            state.new_tab_menu_open = !state.new_tab_menu_open;
+
+            Command::none()
+        }
+        Message::NewCollection => {
+            let name = format!(
+                "Collection {}",
+                state.tabs.iter().filter(|tab| tab.is_collection()).count() + 1
+            );
+            println!("Creating new collection: {}", name);
+
+            state.new_tab_menu_open = false;
+            state.add_tab(Tab::new_collection(name));
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::NewSmartTab => {
+            let name = format!(
+                "Smart tab {}",
+                state.tabs.iter().filter(|tab| tab.is_smart()).count() + 1
+            );
+            println!("Creating new smart tab: {}", name);
+
+            state.new_tab_menu_open = false;
+            state.add_tab(Tab::new_smart(name, String::new()));
+            state.load_missing_clips(); // TODO: move to async
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::SmartQueryChanged(index, text) => {
+            if let Some(Tab {
+                kind: TabKind::Smart { query },
+                ..
+            }) = state.tabs.get_mut(*index)
+            {
+                *query = text.to_owned();
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::PickCollectionFiles => {
+            println!("Presenting audio file picker...");
+
+            let tab_idx = state.current_tab;
+            Command::perform(get_audio_files_async(), move |paths| {
+                Message::AddToCollection(tab_idx, paths)
+            })
+        }
+        Message::AddToCollection(index, paths) => {
+            if let Some(tab) = state.tabs.get_mut(*index) {
+                println!("Adding {} file(s) to {}", paths.len(), tab.name);
+
+                tab.add_to_collection(paths);
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::RemoveFromCollection(index, path) => {
+            if let Some(tab) = state.tabs.get_mut(*index) {
+                tab.remove_from_collection(path);
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::MoveInCollection(index, path, offset) => {
+            if let Some(tab) = state.tabs.get_mut(*index) {
+                tab.move_in_collection(path, *offset);
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::FilesHovered => {
+            state.drop_hovered = true;
+            state.last_import = None;
+
+            Command::none()
+        }
+        Message::FilesHoveredLeft => {
+            state.drop_hovered = false;
+
+            Command::none()
+        }
+        Message::TabBarHovered(hovered) => {
+            state.tab_bar_hovered = *hovered;
+
+            Command::none()
+        }
+        Message::FileDropped(path) => {
+            state.drop_hovered = false;
+
+            let index = state.current_tab;
+            let current = state
+                .get_current_tab()
+                .filter(|_| !state.favorites_selected)
+                .map(|tab| tab.kind.clone());
+
+            // folders dropped on the tab bar, or where clips cannot go, become new tabs
+            let is_folder = path.is_dir();
+            let accepts_clips = matches!(
+                current,
+                Some(TabKind::Directory { .. } | TabKind::Collection { .. })
+            );
+            if is_folder && (state.tab_bar_hovered || !accepts_clips) {
+                println!("Creating new tab from dropped folder: {:?}", path);
+
+                state.add_tab(Tab::new_directory(path.to_path_buf()));
+                state.set_dirty();
+                state.refresh_clips(); // TODO: move to async
+
+                return Command::none();
+            }
+
+            match current {
+                Some(TabKind::Directory { directory }) => {
+                    println!("Importing {:?} into {:?}", path, directory);
+
+                    Command::perform(
+                        files::import(vec![path.clone()], directory, state.import_mode),
+                        move |(imported, errors)| Message::Imported(index, imported, errors),
+                    )
+                }
+                Some(TabKind::Collection { .. }) => {
+                    let paths = files::audio_files_in(path);
+                    if let Some(tab) = state.tabs.get_mut(index) {
+                        println!("Files dropped into {}: {:?}", tab.name, paths);
+
+                        tab.add_to_collection(&paths);
+                        state.record_import(index, paths.len(), &[]);
+                        state.set_dirty();
+                    }
+
+                    Command::none()
+                }
+                _ => {
+                    println!("Dropped file ignored, no tab to import into: {:?}", path);
+
+                    Command::none()
+                }
+            }
+        }
+        Message::Imported(index, imported, errors) => {
+            for error in errors.iter() {
+                println!("Could not import {}", error);
+            }
+
+            if let Some(tab) = state.tabs.get_mut(*index) {
+                tab.load_clips(); // TODO: move to async
+            }
+            state.record_import(*index, imported.len(), errors);
+
+            Command::none()
+        }
+        Message::SetImportMode(mode) => {
+            state.import_mode = *mode;
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::DismissImport => {
+            state.last_import = None;
+
+            Command::none()
+        }
+        Message::NewTab => {
+            println!("New tab");
+            println!("Presenting directory file picker...");
+
+            state.new_tab_menu_open = false;
+
+            Command::perform(get_dir_async(), Message::CreateTab)
+        }
+        Message::CreateTab(path) => {
+            if let Some(path) = path {
+                println!("Creating new tab with path: {:?}", path);
+
+                state.add_tab(Tab::new_directory(path.to_path_buf()));
+                state.set_dirty();
+                state.refresh_clips(); // TODO: move to async
+            } else {
+                println!("No path provided, tab not created");
+            }
+
+            Command::none()
+        }
+        Message::RefreshClips => {
+            if state.favorites_selected {
+                state.refresh_all_clips();
+            } else {
+                state.refresh_clips();
+            }
+
+            Command::none()
+        }
+        Message::ToggleSortMenu => {
+            state.sort_menu_open = !state.sort_menu_open;
+
+            Command::none()
+        }
+        Message::SetSortMode(mode) => {
+            state.sort_menu_open = false;
+            state.set_sort_mode(*mode);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::DragStart(path) => {
+            state.dragging = Some(path.clone());
+            state.drag_target = Some(path.clone());
+
+            Command::none()
+        }
+        Message::DragOver(path) => {
+            if state.dragging.is_some() {
+                state.drag_target = Some(path.clone());
+            }
+
+            Command::none()
+        }
+        Message::DragEnd => {
+            if state.finish_drag() || state.finish_tab_drag() {
+                state.set_dirty();
+            }
+
+            Command::none()
+        }
+        Message::WindowResized(size) => {
+            state.window_size = *size;
+
+            Command::none()
+        }
+        Message::ToggleLayoutMenu => {
+            state.layout_menu_open = !state.layout_menu_open;
+
+            Command::none()
+        }
+        Message::SetLayout(layout) => {
+            state.layout_menu_open = false;
+            state.set_layout(*layout);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::SetGridColumns(columns) => {
+            state.layout_menu_open = false;
+            state.set_grid_columns(*columns);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::EditClipLabel(path) => {
+            let label = state
+                .get_clip_settings(path)
+                .and_then(|settings| settings.label.clone())
+                .unwrap_or_default();
+            state.label_edit = Some((path.clone(), label));
+
+            widget::text_input::focus(label_input_id())
+        }
+        Message::ClipLabelChanged(label) => {
+            if let Some((_, text)) = &mut state.label_edit {
+                *text = label.clone();
+            }
 
-        widget::row!(button, label)
-            .width(Length::Shrink)
-            .spacing(SPACING_SMALL)
-            .align_items(Alignment::Center)
-    };
+            Command::none()
+        }
+        Message::SubmitClipLabel => {
+            if let Some((path, label)) = state.label_edit.take() {
+                state.set_clip_label(&path, &label);
+                state.set_dirty();
+            }
 
-    let slider = widget::slider(range, value, on_change_message)
-        // .width(Length::Fixed(256.0))
-        .step(0.01)
-        .on_release(Message::SetDirty)
-        .style(style::slider::CustomSlider::default());
+            Command::none()
+        }
+        Message::CancelClipLabel => {
+            state.label_edit = None;
 
-    let value_text = widget::text(format!("{:.0}%", value * 100.0))
-        .horizontal_alignment(alignment::Horizontal::Left);
+            Command::none()
+        }
+        Message::StartRename(path) => {
+            state.file_error = None;
+            state.file_edit = Some(FileEdit::Rename {
+                path: path.clone(),
+                name: file_name_label(path),
+            });
+
+            widget::text_input::focus(rename_input_id())
+        }
+        Message::RenameChanged(name) => {
+            if let Some(FileEdit::Rename { name: text, .. }) = &mut state.file_edit {
+                *text = name.clone();
+            }
 
-    widget::row!(label_row, slider, value_text)
-        .width(Length::Fixed(384.0))
-        .spacing(SPACING_NORMAL)
-        .align_items(Alignment::Center)
-}
+            Command::none()
+        }
+        Message::RequestMove(path, tab) => {
+            state.file_error = None;
+            state.file_edit = Some(FileEdit::Move {
+                path: path.clone(),
+                tab: *tab,
+            });
 
-fn format_seconds_to_time(seconds: f64) -> String {
-    let total_seconds = seconds as u64;
-    let minutes = total_seconds / 60;
-    let seconds = total_seconds % 60;
+            Command::none()
+        }
+        Message::RequestTrash(path) => {
+            state.file_error = None;
+            state.file_edit = Some(FileEdit::Trash { path: path.clone() });
 
-    format!("{:02}:{:02}", minutes, seconds)
-}
+            Command::none()
+        }
+        Message::ConfirmFileEdit => {
+            match state.apply_file_edit() {
+                Ok(()) => state.set_dirty(),
+                Err(error) => {
+                    println!("Could not change file: {}", error);
+                    state.file_error = Some(error.to_string());
+                }
+            }
+            // a clip trashed before can't be brought back with undo anymore
+            state.release_trash();
 
-fn truncate_text(text: &str, max_length: usize) -> String {
-    if text.len() > max_length && max_length > 3 {
-        format!("{}...", &text[..max_length - 3])
-    } else {
-        text.to_string()
-    }
-}
+            Command::none()
+        }
+        Message::CancelFileEdit => {
+            state.file_edit = None;
 
-fn scrollable_properties() -> Properties {
-    Properties::default().scroller_width(4.0).width(4)
-}
+            Command::none()
+        }
+        Message::UndoFileOperation => {
+            match state.undo_file_operation() {
+                Ok(()) => state.set_dirty(),
+                Err(error) => {
+                    println!("Could not undo file change: {}", error);
+                    state.file_error = Some(error.to_string());
+                }
+            }
 
-async fn get_dir_async() -> Option<std::path::PathBuf> {
-    let folder = rfd::AsyncFileDialog::new().pick_folder().await;
-    let path = folder.map(|handle| handle.path().to_path_buf());
+            Command::none()
+        }
+        Message::DismissFileOperation => {
+            state.file_error = None;
+            state.last_file_operation = None;
+            state.release_trash();
 
-    if let Some(path) = path {
-        Some(path)
-    } else {
-        None
-    }
-}
+            Command::none()
+        }
+        Message::ToggleDuplicates => {
+            state.duplicates_open = !state.duplicates_open;
+            state.pending_delete = None;
+            state.set_dirty();
 
-pub fn update(state: &mut AppState, message: &Message) -> Command<Message> {
-    let command = match message {
-        Message::Saved(_) => {
-            println!("Saved!");
-            state.save();
+            Command::none()
+        }
+        Message::CompareAudioToggled(enabled) => {
+            state.compare_audio = *enabled;
 
             Command::none()
         }
-        Message::SelectTab(index) => {
-            println!("Tab selected: {}", index);
+        Message::ScanDuplicates => {
+            state.scanning_duplicates = true;
+            state.pending_delete = None;
+
+            Command::perform(
+                duplicates::scan(state.all_clip_paths(), state.compare_audio),
+                Message::DuplicatesFound,
+            )
+        }
+        Message::DuplicatesFound(groups) => {
+            println!("Found {} groups of duplicates", groups.len());
+
+            state.scanning_duplicates = false;
+            state.duplicate_groups = Some(groups.clone());
+
+            Command::none()
+        }
+        Message::HideClip(path) => {
+            state.hide_clip(path);
+            state.set_dirty();
 
-            state.select_tab(*index);
+            Command::none()
+        }
+        Message::UnhideClip(path) => {
+            state.hidden_clips.remove(path);
             state.set_dirty();
 
-            if state.get_current_tab().unwrap().clips.is_empty() {
-                println!("Tab is empty, refreshing clips...");
-                state.refresh_clips(); // TODO: move to async
+            Command::none()
+        }
+        Message::HideExtraCopies(group) => {
+            state.hide_extra_copies(*group);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::RequestDelete(path) => {
+            state.pending_delete = Some(path.clone());
+
+            Command::none()
+        }
+        Message::ConfirmDelete => {
+            if let Some(path) = state.pending_delete.take() {
+                match state.delete_clip(&path) {
+                    Ok(()) => {
+                        println!("Deleted {}", path.display());
+                        state.set_dirty();
+                    }
+                    Err(error) => println!("Could not delete {}: {}", path.display(), error),
+                }
             }
 
             Command::none()
         }
-        Message::CloseTab(index) => {
-            println!("Tab closed: {}", index);
+        Message::CancelDelete => {
+            state.pending_delete = None;
 
-            state.close_tab(*index);
+            Command::none()
+        }
+        Message::ToggleHistory => {
+            state.history_open = !state.history_open;
             state.set_dirty();
 
             Command::none()
         }
-        Message::NewTab => {
-            println!("New tab");
-            println!("Presenting directory file picker...");
-
-            Command::perform(get_dir_async(), Message::CreateTab)
+        Message::ExportStats(format) => {
+            let report = state.stats_report();
+            let contents = match format {
+                ReportFormat::Csv => history::to_csv(&report),
+                ReportFormat::Json => serde_json::to_string_pretty(&report).unwrap_or_default(),
+            };
+
+            Command::perform(
+                export_stats_async(contents, *format),
+                Message::StatsExported,
+            )
         }
-        Message::CreateTab(path) => {
+        Message::StatsExported(path) => {
             if let Some(path) = path {
-                println!("Creating new tab with path: {:?}", path);
+                println!("Stats exported to {}", path.display());
+            }
 
-                state.add_tab(Tab {
-                    name: path.file_name().unwrap().to_str().unwrap().to_owned(),
-                    directory: path.to_path_buf(),
-                    clips: vec![],
-                });
+            Command::none()
+        }
+        Message::ToggleMetadata => {
+            state.toggle_metadata();
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::SelectFavorites => {
+            println!("Favorites selected");
+
+            state.select_favorites();
+            state.set_dirty();
+
+            restore_scroll(state)
+        }
+        Message::ToggleFavorite(path) => {
+            state.toggle_favorite(path);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::ToggleClipSelected(path) => {
+            state.toggle_clip_selected(path);
+
+            Command::none()
+        }
+        Message::SelectAllClips => {
+            state.select_all_clips();
+
+            Command::none()
+        }
+        Message::ClearSelection => {
+            state.selected_clips.clear();
+
+            Command::none()
+        }
+        Message::TagInputChanged(tag) => {
+            state.tag_input = tag.to_owned();
+
+            Command::none()
+        }
+        Message::AddTagToSelection => {
+            let tag = std::mem::take(&mut state.tag_input);
+            state.add_tag_to_selection(&tag);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::RemoveTagFromSelection => {
+            let tag = std::mem::take(&mut state.tag_input);
+            state.remove_tag_from_selection(&tag);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::SetSelectionColor(color) => {
+            state.set_selection_color(*color);
+            state.set_dirty();
+
+            Command::none()
+        }
+        Message::ToggleTagFilter(tag) => {
+            state.toggle_tag_filter(tag);
+
+            Command::none()
+        }
+        Message::SearchChanged(query) => {
+            state.search_query = query.to_owned();
+
+            Command::none()
+        }
+        Message::SearchScopeToggled => {
+            state.search_all_tabs = !state.search_all_tabs;
+            if state.search_all_tabs {
+                state.load_missing_clips(); // TODO: move to async
+            }
+
+            Command::none()
+        }
+        Message::SearchSubmitted if state.is_searching() => {
+            let top_hit = state
+                .search_results()
+                .first()
+                .map(|(_, clip)| (*clip).clone());
+
+            if let Some(clip) = top_hit {
+                println!("Playing top search hit: {}", clip.name);
+                state.start_playback(clip);
+            }
+
+            Command::none()
+        }
+        Message::FocusSearch => widget::text_input::focus(search_input_id()),
+        Message::ClearSearch => {
+            state.search_query.clear();
+            state.tab_rename = None;
+
+            Command::none()
+        }
+        Message::DismissNotification(index) => {
+            if *index < state.notifications.len() {
+                state.notifications.remove(*index);
+            }
+
+            Command::none()
+        }
+        Message::RestoreBackup => Command::perform(get_backup_async(), Message::BackupChosen),
+        Message::BackupChosen(Some(path)) => {
+            Command::perform(SavedState::load_backup(path.clone()), Message::BackupLoaded)
+        }
+        Message::BackupChosen(None) => Command::none(),
+        Message::BackupLoaded(result) => match result {
+            Ok(saved) => {
+                println!("Restoring backup");
+
+                let command = replace_state(state, state.profile.clone(), Some(saved.clone()));
                 state.set_dirty();
-                state.refresh_clips(); // TODO: move to async
-            } else {
-                println!("No path provided, tab not created");
+
+                command
+            }
+            Err(error) => {
+                state.notify(format!("Could not restore the backup. {error}"), None);
+
+                Command::none()
+            }
+        },
+        Message::ExportPack(index) => {
+            state.profile_menu_open = false;
+
+            let (tabs, name) = match index {
+                Some(index) => match state.tabs.get(*index) {
+                    Some(tab) => (vec![tab.clone()], tab.name.clone()),
+                    None => return Command::none(),
+                },
+                None => (state.tabs.clone(), state.profile.clone()),
+            };
+            println!("Exporting {} tab(s) as a board pack...", tabs.len());
+
+            Command::perform(
+                export_pack_async(tabs, state.clip_settings.clone(), name),
+                Message::PackExported,
+            )
+        }
+        Message::PackExported(result) => {
+            match result {
+                Some(Ok(path)) => println!("Board pack exported to {}", path.display()),
+                Some(Err(error)) => {
+                    state.notify(format!("Could not export the board pack. {error}"), None)
+                }
+                None => {}
             }
 
             Command::none()
         }
-        Message::RefreshClips => {
-            state.refresh_clips();
+        Message::ImportPack => {
+            state.new_tab_menu_open = false;
+
+            Command::perform(import_pack_async(), Message::PackImported)
+        }
+        Message::PackImported(result) => {
+            match result {
+                Some(Ok(board)) => {
+                    println!(
+                        "Unpacked {} tab(s) into {}",
+                        board.tabs.len(),
+                        board.source.display()
+                    );
+
+                    let first = state.add_board(board.clone());
+                    state.set_dirty();
+
+                    return open_tab(state, first);
+                }
+                Some(Err(error)) => {
+                    state.notify(format!("Could not import the board pack. {error}"), None)
+                }
+                None => {}
+            }
+
+            Command::none()
+        }
+        Message::ImportBoard | Message::ImportCategories => {
+            state.new_tab_menu_open = false;
+
+            let folder = matches!(message, Message::ImportCategories);
+            Command::perform(import_board_async(folder), Message::BoardImported)
+        }
+        Message::BoardImported(result) => {
+            match result {
+                Some(Ok(board)) => {
+                    println!(
+                        "Imported {} tab(s) from {}",
+                        board.tabs.len(),
+                        board.source.display()
+                    );
+
+                    let first = state.add_board(board.clone());
+                    state.set_dirty();
+
+                    return open_tab(state, first);
+                }
+                Some(Err(error)) => {
+                    state.notify(format!("Could not import the board. {error}"), None)
+                }
+                None => {}
+            }
+
+            Command::none()
+        }
+        Message::ToggleProfileMenu => {
+            state.profile_menu_open = !state.profile_menu_open;
+            if state.profile_menu_open {
+                state.profiles = saving::profiles();
+                if !state.profiles.contains(&state.profile) {
+                    state.profiles.push(state.profile.clone());
+                }
+            }
+
+            Command::none()
+        }
+        Message::SwitchProfile(name) => {
+            state.profile_menu_open = false;
+
+            switch_profile(state, name.clone())
+        }
+        Message::NewProfile => {
+            state.profile_menu_open = false;
+            state.new_profile_name = Some(String::new());
+
+            widget::text_input::focus(profile_input_id())
+        }
+        Message::NewProfileNameChanged(name) => {
+            state.new_profile_name = Some(name.clone());
+
+            Command::none()
+        }
+        Message::CreateProfile => {
+            let name = state
+                .new_profile_name
+                .as_deref()
+                .map(str::trim)
+                .unwrap_or_default()
+                .to_owned();
+            if name.is_empty() {
+                return Command::none();
+            }
+            state.new_profile_name = None;
+
+            switch_profile(state, name)
+        }
+        Message::CancelNewProfile => {
+            state.new_profile_name = None;
 
             Command::none()
         }
+        Message::Loaded(profile, result) => {
+            let command = match result {
+                Ok(saved) => replace_state(state, profile.clone(), Some(saved.clone())),
+                // a profile without a config yet starts out empty
+                Err(LoadError::NotFound) => {
+                    let command = replace_state(state, profile.clone(), None);
+                    state.set_dirty();
+                    command
+                }
+                Err(error) => {
+                    state.notify(format!("Could not switch to {profile}. {error}"), None);
+                    return Command::none();
+                }
+            };
+            println!("Switched to profile {}", profile);
+            saving::remember_profile(profile);
+
+            command
+        }
         Message::SetDirty => {
             state.set_dirty();
 
@@ -505,22 +3198,95 @@ pub fn update(state: &mut AppState, message: &Message) -> Command<Message> {
         _ => Command::none(),
     };
 
-    let save = if state.dirty && !state.saving {
-        state.saving = true;
-
-        Command::perform(
-            SavedState {
-                tabs: state.tabs.to_vec(),
-                current_tab: state.current_tab,
-                global_volume: state.get_global_volume(),
-                global_speed: state.get_global_speed(),
-            }
-            .save(),
-            Message::Saved,
-        )
+    let save = if state.should_save() {
+        save_command(state)
     } else {
         Command::none()
     };
 
     Command::batch(vec![command, save])
 }
+
+/// Swaps in another config while the app runs, or a fresh state when there
+/// is none, keeping the window as it is.
+fn replace_state(
+    state: &mut AppState,
+    profile: String,
+    saved: Option<SavedState>,
+) -> Command<Message> {
+    state.stop_all_playbacks();
+
+    let audio_manager = state.audio_manager.take();
+    let (window_size, window_position) = (state.window_size, state.window_position);
+    *state = match saved {
+        Some(saved) => AppState::from_saved(profile, saved, audio_manager),
+        None => AppState {
+            profile,
+            audio_manager,
+            ..Default::default()
+        },
+    };
+    state.window_size = window_size;
+    state.window_position = window_position;
+    // the restored state comes first, the settings file is made to match it
+    state.overwrite_settings();
+
+    restore_scroll(state)
+}
+
+/// Saves what is left to save of the current profile and loads another one.
+fn switch_profile(state: &mut AppState, profile: String) -> Command<Message> {
+    if profile == state.profile {
+        return Command::none();
+    }
+
+    state.switching_to = Some(profile);
+    if state.saving {
+        // carries on once the save underway is done
+        return Command::none();
+    }
+
+    continue_switch(state)
+}
+
+/// Takes the next step of switching profiles, if one is underway: saving
+/// what changed since the last save, and once nothing did, loading the
+/// other profile.
+fn continue_switch(state: &mut AppState) -> Command<Message> {
+    let Some(profile) = state.switching_to.clone() else {
+        return Command::none();
+    };
+    if state.has_unsaved_changes() && state.can_save {
+        return save_command(state);
+    }
+
+    state.switching_to = None;
+    Command::perform(SavedState::load(profile.clone()), move |result| {
+        Message::Loaded(profile, result)
+    })
+}
+
+/// Writes the current state to the profile's config in the background.
+fn save_command(state: &mut AppState) -> Command<Message> {
+    state.saving = true;
+    state.write_settings();
+
+    let profile = state.profile.clone();
+    Command::perform(state.saved_state().save(profile.clone()), move |result| {
+        Message::Saved(profile, result)
+    })
+}
+
+/// Saves everything once more, the session included, then closes the window.
+fn close_command(state: &AppState) -> Command<Message> {
+    if !state.can_save {
+        return window::close(window::Id::MAIN);
+    }
+
+    Command::perform(state.saved_state().save(state.profile.clone()), |result| {
+        if let Err(error) = result {
+            println!("Could not save before closing: {}", error);
+        }
+        Message::Close
+    })
+}